@@ -1,110 +1,209 @@
-use std::{fs, io::Cursor};
+use std::{collections::HashMap, fs, path::PathBuf};
 
-use image::{ImageFormat, ImageReader};
-use reqwest::Client;
 use whiskers_launcher_core::{
     features::{
         core::extensions::{get_extension_request, get_form_response},
-        extensions::ExtensionRequest,
+        extensions::{ExtensionRequest, FormResult},
     },
     utils::send_notification,
 };
 
 use crate::{
-    paths::get_favicons_dir,
+    error::{get_id_arg, required_field, Error, Result},
+    export::to_netscape_html,
+    favicons::{fetch_favicon, fetch_image_icon, remove_favicons},
+    import::{apply_import, read_import_file},
+    launch::{open_url, validate_launch_command},
+    links::check_links,
+    pages::{fetch_page_details, PageDetails},
     settings::{
-        functions::{get_settings, write_settings},
-        Bookmark, Group,
+        functions::{convert_settings, get_settings, lock_settings, write_settings},
+        parse_aliases, parse_keyword, parse_launch_command, parse_tags,
+        storage::SettingsFormat,
+        trash::{get_trash_retention, TrashEntry},
+        ActionMode, AliasOwner, Bookmark, Group, LinkCheck,
     },
+    urls::{canonicalize_url, parse_url},
+    usage::{get_now, record_bookmark_open, record_group_open},
 };
 
 pub async fn on_run_commands(request: ExtensionRequest) {
-    let command = request.command.unwrap();
+    if let Err(error) = run_command(request).await {
+        send_notification("Error", error.to_string());
+    }
+}
+
+async fn run_command(request: ExtensionRequest) -> Result<()> {
+    let command = request
+        .command
+        .ok_or_else(|| Error::MissingArg("command".to_string()))?;
+
+    // Opening doesn't change the settings and locks the usage on its own, and checking links
+    // locks the settings just to save the results, so a long check doesn't block every click
+    match command.as_str() {
+        "open-bookmark" => return open_bookmark(),
+        "open-group" => return open_group(),
+        "check-links" => return check_bookmark_links().await,
+        _ => {}
+    }
+
+    let _lock = lock_settings()?;
+
+    purge_trash()?;
 
     match command.as_str() {
         "create-bookmark" => create_bookmark().await,
         "create-group" => create_group(),
         "edit-bookmark" => edit_bookmark().await,
         "edit-group" => edit_group(),
-        "open-group" => open_group(),
         "delete-bookmark" => delete_bookmark(),
         "delete-group" => delete_group(),
-        _ => {}
-    };
+        "convert-settings" => convert_settings_format(),
+        "import-bookmarks" => import_bookmarks(),
+        "import-browser" => import_browser(),
+        "export-bookmarks" => export_bookmarks(),
+        "update-redirect" => update_redirect(),
+        "merge-duplicates" => merge_duplicates(),
+        "restore" => restore_item(),
+        "delete-forever" => delete_item_forever(),
+        _ => Err(Error::UnknownCommand(command)),
+    }
 }
 
-async fn create_bookmark() {
+async fn create_bookmark() -> Result<()> {
     let response = get_form_response();
-    let name = response.get_result("name").unwrap().field_value;
-    let url = response.get_result("url").unwrap().field_value;
-
-    let mut settings = get_settings();
-    let mut bookmark = Bookmark::new(&name, &url);
-
-    if response.get_result("use-icon").unwrap().as_bool() {
-        let url = format!("https://www.google.com/s2/favicons?domain={}&sz=256", &url);
-
-        let request = Client::new().get(&url).send().await;
-
-        match request {
-            Ok(response) => {
-                if response.status().is_success() {
-                    let bytes = response.bytes().await.unwrap();
-
-                    let mut path = get_favicons_dir();
-
-                    if !path.exists() {
-                        fs::create_dir_all(&path).expect("Error creating directory");
-                    }
-
-                    path.push(format!("{}.png", bookmark.id));
-
-                    let image = ImageReader::new(Cursor::new(&bytes))
-                        .with_guessed_format()
-                        .unwrap()
-                        .decode()
-                        .unwrap();
-
-                    image
-                        .save_with_format(&path, ImageFormat::Png)
-                        .expect("Error saving image");
-
-                    bookmark =
-                        bookmark.set_icon_path(&path.into_os_string().into_string().unwrap());
-                }
-            }
-            Err(_) => {
-                send_notification(
-                    "Error",
-                    "Error getting icon. Make sure you have a valid url and internet connection",
-                );
-            }
+    let name = required_field(response.get_result("name"), "name")?.field_value;
+    let url = canonicalize_url(&required_field(response.get_result("url"), "url")?.field_value)?;
+    let tags = required_field(response.get_result("tags"), "tags")?.field_value;
+    let keyword = required_field(response.get_result("keyword"), "keyword")?.field_value;
+    let aliases = required_field(response.get_result("aliases"), "aliases")?.field_value;
+    let description =
+        required_field(response.get_result("description"), "description")?.field_value;
+    let action_mode =
+        required_field(response.get_result("action-mode"), "action-mode")?.field_value;
+    let launch_command = parse_launch_command(
+        &required_field(response.get_result("launch-command"), "launch-command")?.field_value,
+    );
+    let use_icon = required_field(response.get_result("use-icon"), "use-icon")?.as_bool();
+    let fetch_details =
+        required_field(response.get_result("fetch-details"), "fetch-details")?.as_bool();
+
+    if let Some(launch_command) = &launch_command {
+        validate_launch_command(launch_command)?;
+    }
+
+    let details = if fetch_details || name.trim().is_empty() {
+        // The details only fill in what was left empty, so failing isn't fatal
+        fetch_page_details(&url).await.unwrap_or_else(|error| {
+            send_notification("Error", error.to_string());
+            PageDetails::default()
+        })
+    } else {
+        PageDetails::default()
+    };
+
+    let name = if name.trim().is_empty() {
+        details
+            .title
+            .to_owned()
+            .unwrap_or_else(|| get_url_name(&url))
+    } else {
+        name
+    };
+
+    let description = if description.trim().is_empty() {
+        details.description.to_owned().unwrap_or_default()
+    } else {
+        description
+    };
+
+    let mut settings = get_settings()?;
+    let keyword = parse_keyword(&keyword);
+    let mut bookmark = Bookmark::new(settings.next_bookmark_id(), &name, &url)
+        .set_tags(parse_tags(&tags))
+        .set_keyword(keyword.to_owned())
+        .set_aliases(parse_aliases(&aliases))
+        .set_description(description)
+        .set_action_mode(ActionMode::from_id(&action_mode))
+        .set_launch_command(launch_command);
+
+    if let Some(duplicate) = settings.find_duplicate(bookmark.id, &url) {
+        return Err(Error::DuplicateUrl(duplicate.name.to_owned()));
+    }
+
+    if let Some(keyword) = &keyword {
+        settings.validate_keyword(bookmark.id, keyword)?;
+    }
+
+    settings.validate_aliases(AliasOwner::Bookmark(bookmark.id), &bookmark.aliases)?;
+
+    if use_icon {
+        // A missing icon shouldn't stop the bookmark from being created
+        match fetch_icon(bookmark.id, &url, &details).await {
+            Ok(path) => bookmark = bookmark.set_icon_path(path),
+            Err(error) => send_notification("Error", error.to_string()),
         }
     }
 
     settings.bookmarks.push(bookmark);
 
-    write_settings(settings);
+    write_settings(settings)?;
 
     send_notification("Create bookmark", "Bookmark created successfully");
+
+    Ok(())
 }
 
-fn create_group() {
-    let mut settings = get_settings();
-    let response = get_form_response();
-    let name = response.get_result("name").unwrap().field_value;
-    let icon_path = response.get_result("icon-path").unwrap().field_value;
-    let tint_icon = response.get_result("tint-icon").unwrap().field_value;
-    let results = response.results;
-    let mut bookmarks_ids = Vec::<usize>::new();
+/// Uses the page `og:image` when the website has no icon
+async fn fetch_icon(bookmark_id: usize, url: &str, details: &PageDetails) -> Result<String> {
+    let error = match fetch_favicon(bookmark_id, url).await {
+        Ok(path) => return Ok(path),
+        Err(error) => error,
+    };
 
-    for result in results {
-        if result.as_bool() {
-            bookmarks_ids.push(result.field_id.parse().unwrap());
-        }
+    match &details.image_url {
+        Some(image_url) => fetch_image_icon(bookmark_id, image_url.as_str()).await,
+        None => Err(error),
     }
+}
+
+/// Name for bookmarks whose page has no title, like `github.com`
+fn get_url_name(url: &str) -> String {
+    parse_url(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+        .unwrap_or_else(|| url.to_string())
+}
 
-    let mut group = Group::new(name, bookmarks_ids).set_tint_icon(tint_icon == "true");
+fn create_group() -> Result<()> {
+    let mut settings = get_settings()?;
+    let response = get_form_response();
+    let name = required_field(response.get_result("name"), "name")?.field_value;
+    let icon_path = required_field(response.get_result("icon-path"), "icon-path")?.field_value;
+    let tint_icon = required_field(response.get_result("tint-icon"), "tint-icon")?.field_value;
+    let aliases = required_field(response.get_result("aliases"), "aliases")?.field_value;
+    let description =
+        required_field(response.get_result("description"), "description")?.field_value;
+    let action_mode =
+        required_field(response.get_result("action-mode"), "action-mode")?.field_value;
+    let launch_command = parse_launch_command(
+        &required_field(response.get_result("launch-command"), "launch-command")?.field_value,
+    );
+    let (bookmarks_ids, groups_ids) = get_group_members(&response.results);
+
+    if let Some(launch_command) = &launch_command {
+        validate_launch_command(launch_command)?;
+    }
+
+    let mut group = Group::new(settings.next_group_id(), name, bookmarks_ids)
+        .set_groups_ids(groups_ids)
+        .set_aliases(parse_aliases(&aliases))
+        .set_description(description)
+        .set_action_mode(ActionMode::from_id(&action_mode))
+        .set_launch_command(launch_command)
+        .set_tint_icon(tint_icon == "true");
+
+    settings.validate_aliases(AliasOwner::Group(group.id), &group.aliases)?;
 
     if !icon_path.is_empty() {
         group = group.set_icon_path(icon_path);
@@ -112,148 +211,477 @@ fn create_group() {
 
     settings.groups.push(group);
 
-    write_settings(settings);
+    write_settings(settings)?;
+
+    send_notification("Create group", "Group created successfully");
+
+    Ok(())
 }
 
-fn edit_group() {}
+/// The ids of the bookmarks and groups toggled on in a group form
+fn get_group_members(results: &[FormResult]) -> (Vec<usize>, Vec<usize>) {
+    let mut bookmarks_ids = Vec::<usize>::new();
+    let mut groups_ids = Vec::<usize>::new();
+
+    for result in results {
+        if !result.as_bool() {
+            continue;
+        }
+
+        // Member groups use `group-{id}` so they don't clash with the bookmark ids
+        match result.field_id.strip_prefix("group-") {
+            Some(group_id) => groups_ids.extend(group_id.parse::<usize>().ok()),
+            None => bookmarks_ids.extend(result.field_id.parse::<usize>().ok()),
+        }
+    }
 
-async fn edit_bookmark() {
+    (bookmarks_ids, groups_ids)
+}
+
+fn edit_group() -> Result<()> {
     let response = get_form_response();
-    let name = response.get_result("name").unwrap().field_value;
-    let url = response.get_result("url").unwrap().field_value;
-    let use_icon = response.get_result("use-icon").unwrap().field_value;
-    let bookmark_id: usize = response.args[0].parse().unwrap();
-
-    let mut settings = get_settings();
-    let mut bookmarks = Vec::<Bookmark>::new();
-
-    for bookmark in settings.bookmarks {
-        if bookmark.id == bookmark_id {
-            let mut path = get_favicons_dir();
-
-            if use_icon == "true" {
-                let url = format!("https://www.google.com/s2/favicons?domain={}&sz=256", &url);
-
-                let request = Client::new().get(&url).send().await;
-
-                match request {
-                    Ok(response) => {
-                        if response.status().is_success() {
-                            let bytes = response.bytes().await.unwrap();
-
-                            if !path.exists() {
-                                fs::create_dir_all(&path).expect("Error creating directory");
-                            }
-
-                            path.push(format!("{}.png", bookmark.id));
-
-                            let image = ImageReader::new(Cursor::new(&bytes))
-                                .with_guessed_format()
-                                .unwrap()
-                                .decode()
-                                .unwrap();
-
-                            image
-                                .save_with_format(&path, ImageFormat::Png)
-                                .expect("Error saving image");
-                        }
-                    }
-                    Err(_) => {
-                        send_notification(
-                            "Error",
-                            "Error getting icon. Make sure you have a valid url and internet connection");
-                    }
-                }
-            }
-
-            bookmarks.push(Bookmark {
-                id: bookmark_id.to_owned(),
-                icon_path: if use_icon == "true" {
-                    Some(path.into_os_string().into_string().unwrap())
-                } else {
-                    None
-                },
-                name: name.to_owned(),
-                url: url.to_owned(),
-            });
-        } else {
-            bookmarks.push(bookmark.to_owned());
+    let name = required_field(response.get_result("name"), "name")?.field_value;
+    let icon_path = required_field(response.get_result("icon-path"), "icon-path")?.field_value;
+    let tint_icon = required_field(response.get_result("tint-icon"), "tint-icon")?.field_value;
+    let aliases = required_field(response.get_result("aliases"), "aliases")?.field_value;
+    let description =
+        required_field(response.get_result("description"), "description")?.field_value;
+    let action_mode =
+        required_field(response.get_result("action-mode"), "action-mode")?.field_value;
+    let launch_command = parse_launch_command(
+        &required_field(response.get_result("launch-command"), "launch-command")?.field_value,
+    );
+    let group_id = get_id_arg(&response.args)?;
+    let (bookmarks_ids, groups_ids) = get_group_members(&response.results);
+
+    if let Some(launch_command) = &launch_command {
+        validate_launch_command(launch_command)?;
+    }
+
+    let mut settings = get_settings()?;
+
+    if settings.creates_cycle(group_id, &groups_ids) {
+        return Err(Error::GroupCycle(name));
+    }
+
+    let aliases = parse_aliases(&aliases);
+
+    settings.validate_aliases(AliasOwner::Group(group_id), &aliases)?;
+
+    let group = settings
+        .groups
+        .iter_mut()
+        .find(|g| g.id == group_id)
+        .ok_or(Error::GroupNotFound(group_id))?;
+
+    group.name = name;
+    group.icon_path = if icon_path.is_empty() {
+        None
+    } else {
+        Some(icon_path)
+    };
+    group.tint_icon = tint_icon == "true";
+    group.bookmarks_ids = bookmarks_ids;
+    group.groups_ids = groups_ids;
+    group.aliases = aliases;
+    group.description = Some(description).filter(|d| !d.trim().is_empty());
+    group.action_mode = ActionMode::from_id(&action_mode);
+    group.launch_command = launch_command;
+
+    write_settings(settings)?;
+
+    send_notification("Edit group", "Group edited successfully");
+
+    Ok(())
+}
+
+async fn edit_bookmark() -> Result<()> {
+    let response = get_form_response();
+    let name = required_field(response.get_result("name"), "name")?.field_value;
+    let url = canonicalize_url(&required_field(response.get_result("url"), "url")?.field_value)?;
+    let tags = required_field(response.get_result("tags"), "tags")?.field_value;
+    let keyword = required_field(response.get_result("keyword"), "keyword")?.field_value;
+    let aliases = required_field(response.get_result("aliases"), "aliases")?.field_value;
+    let description =
+        required_field(response.get_result("description"), "description")?.field_value;
+    let action_mode =
+        required_field(response.get_result("action-mode"), "action-mode")?.field_value;
+    let launch_command = parse_launch_command(
+        &required_field(response.get_result("launch-command"), "launch-command")?.field_value,
+    );
+    let use_icon = required_field(response.get_result("use-icon"), "use-icon")?.as_bool();
+    let bookmark_id = get_id_arg(&response.args)?;
+
+    if let Some(launch_command) = &launch_command {
+        validate_launch_command(launch_command)?;
+    }
+
+    let mut settings = get_settings()?;
+    let keyword = parse_keyword(&keyword);
+    let aliases = parse_aliases(&aliases);
+
+    if let Some(duplicate) = settings.find_duplicate(bookmark_id, &url) {
+        return Err(Error::DuplicateUrl(duplicate.name.to_owned()));
+    }
+
+    if let Some(keyword) = &keyword {
+        settings.validate_keyword(bookmark_id, keyword)?;
+    }
+
+    settings.validate_aliases(AliasOwner::Bookmark(bookmark_id), &aliases)?;
+
+    let bookmark = settings
+        .bookmarks
+        .iter_mut()
+        .find(|b| b.id == bookmark_id)
+        .ok_or(Error::BookmarkNotFound(bookmark_id))?;
+
+    if use_icon {
+        match fetch_favicon(bookmark.id, &url).await {
+            Ok(path) => bookmark.icon_path = Some(path),
+            Err(error) => send_notification("Error", error.to_string()),
         }
+    } else {
+        bookmark.icon_path = None;
     }
 
-    settings.bookmarks = bookmarks;
-    write_settings(settings);
+    // The last check was about the old url
+    if bookmark.url != url {
+        bookmark.link_check = None;
+    }
+
+    bookmark.name = name;
+    bookmark.url = url;
+    bookmark.tags = parse_tags(&tags);
+    bookmark.keyword = keyword;
+    bookmark.aliases = aliases;
+    bookmark.description = Some(description).filter(|d| !d.trim().is_empty());
+    bookmark.action_mode = ActionMode::from_id(&action_mode);
+    bookmark.launch_command = launch_command;
+
+    write_settings(settings)?;
+
+    send_notification("Edit bookmark", "Bookmark edited successfully");
+
+    Ok(())
 }
 
-fn open_group() {
-    let response = get_extension_request();
-    let args = response.args;
-    let group_id: usize = args
-        .get(0)
-        .expect("Expected group id")
-        .parse()
-        .expect("Error parsing group id");
+fn open_group() -> Result<()> {
+    let request = get_extension_request();
+    let group_id = get_id_arg(&request.args)?;
+    let settings = get_settings()?;
 
-    let group = get_settings()
+    let group = settings
         .groups
         .iter()
-        .find(|group| group.id == group_id)
-        .expect("Expected group id")
-        .to_owned();
+        .find(|g| g.id == group_id)
+        .ok_or(Error::GroupNotFound(group_id))?;
+
+    // The group launch command wins over the ones of its bookmarks
+    for bookmark in settings.get_group_bookmarks(group_id) {
+        let launch_command = group
+            .launch_command
+            .as_deref()
+            .or(bookmark.launch_command.as_deref());
+
+        open_url(&bookmark.url, launch_command)?;
+    }
+
+    record_group_open(group_id)
+}
+
+fn open_bookmark() -> Result<()> {
+    let request = get_extension_request();
+    let bookmark_id = get_id_arg(&request.args)?;
+    let settings = get_settings()?;
+
+    let bookmark = settings
+        .bookmarks
+        .iter()
+        .find(|b| b.id == bookmark_id)
+        .ok_or(Error::BookmarkNotFound(bookmark_id))?;
+
+    // Keyword searches pass the query as the second argument
+    let query = request.args.get(1).map(|q| q.as_str()).unwrap_or_default();
+    let url = bookmark.get_url_for(query);
+
+    open_url(&url, bookmark.launch_command.as_deref())?;
+
+    record_bookmark_open(bookmark_id)
+}
 
-    let bookmarks = get_settings().bookmarks;
+fn delete_bookmark() -> Result<()> {
+    let request = get_extension_request();
+    let bookmark_id = get_id_arg(&request.args)?;
+
+    let mut settings = get_settings()?;
+    settings.trash_bookmark(bookmark_id, get_now())?;
+
+    write_settings(settings)?;
+
+    send_notification(
+        "Delete bookmark",
+        "Moved to the trash, type trash to restore it",
+    );
+
+    Ok(())
+}
+
+fn delete_group() -> Result<()> {
+    let request = get_extension_request();
+    let group_id = get_id_arg(&request.args)?;
+
+    let mut settings = get_settings()?;
+    settings.trash_group(group_id, get_now())?;
+
+    write_settings(settings)?;
+
+    send_notification(
+        "Delete group",
+        "Moved to the trash, type trash to restore it",
+    );
+
+    Ok(())
+}
+
+/// Trash commands take the kind of item and its id, like `["bookmark", "3"]`
+fn get_trash_args(args: &[String]) -> Result<(String, usize)> {
+    let kind = args
+        .first()
+        .ok_or_else(|| Error::MissingArg("kind".to_string()))?;
+
+    Ok((kind.to_owned(), get_id_arg(&args[1..])?))
+}
+
+fn restore_item() -> Result<()> {
+    let request = get_extension_request();
+    let (kind, id) = get_trash_args(&request.args)?;
 
-    for bookmark_id in group.bookmarks_ids {
-        if let Some(bookmark) = bookmarks.iter().find(|b| b.id == bookmark_id) {
-            let bookmark = bookmark.to_owned();
+    let mut settings = get_settings()?;
+
+    match kind.as_str() {
+        "group" => settings.restore_group(id)?,
+        "bookmark" => settings.restore_bookmark(id)?,
+        _ => return Err(Error::MissingArg("kind".to_string())),
+    }
+
+    write_settings(settings)?;
+
+    send_notification("Restore", "Restored successfully");
+
+    Ok(())
+}
 
-            std::thread::spawn(move || {
-                open::that(&bookmark.url).expect("Error opening bookmark");
-            });
+fn delete_item_forever() -> Result<()> {
+    let request = get_extension_request();
+    let (kind, id) = get_trash_args(&request.args)?;
+
+    let mut settings = get_settings()?;
+
+    let bookmark = match kind.as_str() {
+        "group" => {
+            settings.delete_trashed_group(id)?;
+            None
         }
+        "bookmark" => Some(settings.delete_trashed_bookmark(id)?),
+        _ => return Err(Error::MissingArg("kind".to_string())),
+    };
+
+    write_settings(settings)?;
+
+    remove_favicons(&bookmark);
+
+    send_notification("Delete forever", "Deleted successfully");
+
+    Ok(())
+}
+
+/// Deletes trashed items older than the `trash-retention` setting
+fn purge_trash() -> Result<()> {
+    let retention_days = match get_trash_retention() {
+        Some(days) => days,
+        None => return Ok(()),
+    };
+
+    let mut settings = get_settings()?;
+
+    let purged = settings.purge_trash(get_now(), retention_days);
+
+    if purged.is_empty() {
+        return Ok(());
     }
+
+    write_settings(settings)?;
+
+    remove_favicons(purged.iter().filter_map(|item| match &item.entry {
+        TrashEntry::Bookmark { bookmark, .. } => Some(bookmark),
+        TrashEntry::Group { .. } => None,
+    }));
+
+    Ok(())
 }
 
-fn delete_bookmark() {
+fn convert_settings_format() -> Result<()> {
     let request = get_extension_request();
-    let args = request.args;
-    let bookmark_id: usize = args
-        .get(0)
-        .expect("Expected bookmark id")
-        .parse()
-        .expect("Error parsing bookmark id");
-
-    let new_bookmarks: Vec<Bookmark> = get_settings()
+    let format = match request.args.first().map(|a| a.as_str()) {
+        Some("text") => SettingsFormat::Text,
+        Some("binary") => SettingsFormat::Binary,
+        _ => return Err(Error::MissingArg("format".to_string())),
+    };
+
+    convert_settings(format)?;
+
+    send_notification("Convert settings", "Settings converted successfully");
+
+    Ok(())
+}
+
+fn import_bookmarks() -> Result<()> {
+    let response = get_form_response();
+    let file_path = required_field(response.get_result("file"), "file")?.field_value;
+
+    import_from_path(PathBuf::from(file_path))
+}
+
+fn import_browser() -> Result<()> {
+    let request = get_extension_request();
+    let file_path = request
+        .args
+        .first()
+        .ok_or_else(|| Error::MissingArg("path".to_string()))?;
+
+    import_from_path(PathBuf::from(file_path))
+}
+
+fn import_from_path(path: PathBuf) -> Result<()> {
+    let tree = read_import_file(&path).map_err(Error::Import)?;
+
+    let mut settings = get_settings()?;
+    let summary = apply_import(&mut settings, tree);
+
+    write_settings(settings)?;
+
+    send_notification(
+        "Import bookmarks",
+        format!(
+            "Imported {} bookmarks and {} groups, {} were already saved",
+            summary.bookmarks, summary.groups, summary.duplicates
+        ),
+    );
+
+    Ok(())
+}
+
+fn export_bookmarks() -> Result<()> {
+    let response = get_form_response();
+    let file_path = required_field(response.get_result("file"), "file")?.field_value;
+    let html = to_netscape_html(&get_settings()?);
+
+    fs::write(&file_path, html)?;
+
+    send_notification(
+        "Export bookmarks",
+        format!("Bookmarks exported to {}", &file_path),
+    );
+
+    Ok(())
+}
+
+async fn check_bookmark_links() -> Result<()> {
+    let checked = get_settings()?.bookmarks;
+    let checks = check_links(&checked).await?;
+
+    // Other commands may have changed the settings during the check, so reload them
+    let _lock = lock_settings()?;
+    let mut settings = get_settings()?;
+
+    let checked_urls: HashMap<usize, &String> = checked.iter().map(|b| (b.id, &b.url)).collect();
+    let mut checks: HashMap<usize, LinkCheck> = checks.into_iter().collect();
+
+    for bookmark in settings.bookmarks.iter_mut() {
+        // Skip bookmarks added or edited in the meantime, the check was about the old url
+        if checked_urls.get(&bookmark.id).copied() != Some(&bookmark.url) {
+            continue;
+        }
+
+        // Links that can't be checked, like mailto ones, lose any older result
+        bookmark.link_check = checks.remove(&bookmark.id);
+    }
+
+    let broken = settings.bookmarks.iter().filter(|b| b.is_broken()).count();
+    let redirected = settings
         .bookmarks
         .iter()
-        .map(|b| b.to_owned())
-        .filter(|b| b.id != bookmark_id)
-        .collect();
+        .filter(|b| b.get_permanent_redirect().is_some())
+        .count();
+
+    write_settings(settings)?;
 
-    let mut settings = get_settings();
-    settings.bookmarks = new_bookmarks;
+    send_notification(
+        "Check links",
+        format!(
+            "Found {} broken and {} moved links, type broken to see them",
+            broken, redirected
+        ),
+    );
 
-    write_settings(settings)
+    Ok(())
 }
 
-fn delete_group() {
+/// Replaces the url of a bookmark with the one it permanently redirects to
+fn update_redirect() -> Result<()> {
     let request = get_extension_request();
-    let args = request.args;
-    let group_id: usize = args
-        .get(0)
-        .expect("Expected group id")
-        .parse()
-        .expect("Error parsing group id");
-
-    let new_groups: Vec<Group> = get_settings()
-        .groups
+    let bookmark_id = get_id_arg(&request.args)?;
+    let mut settings = get_settings()?;
+
+    let bookmark = settings
+        .bookmarks
         .iter()
-        .map(|g| g.to_owned())
-        .filter(|g| g.id != group_id)
-        .collect();
+        .find(|b| b.id == bookmark_id)
+        .ok_or(Error::BookmarkNotFound(bookmark_id))?;
+
+    let redirect_url = bookmark
+        .get_permanent_redirect()
+        .map(|url| url.to_string())
+        .ok_or_else(|| Error::NoRedirect(bookmark.name.to_owned()))?;
+
+    // Bookmarks that moved to the same page would otherwise end up as duplicates
+    if let Some(duplicate) = settings.find_duplicate(bookmark_id, &redirect_url) {
+        return Err(Error::DuplicateUrl(duplicate.name.to_owned()));
+    }
+
+    if let Some(bookmark) = settings.bookmarks.iter_mut().find(|b| b.id == bookmark_id) {
+        bookmark.url = redirect_url;
+        bookmark.link_check = None;
+    }
+
+    write_settings(settings)?;
+
+    send_notification("Update url", "Bookmark url updated successfully");
+
+    Ok(())
+}
+
+/// Merges the bookmarks in the args into the first one
+fn merge_duplicates() -> Result<()> {
+    let request = get_extension_request();
+    let bookmarks_ids = request
+        .args
+        .iter()
+        .map(|arg| {
+            arg.parse::<usize>()
+                .map_err(|_| Error::InvalidId(arg.to_owned()))
+        })
+        .collect::<Result<Vec<usize>>>()?;
+
+    let mut settings = get_settings()?;
+    let merged = settings.merge_bookmarks(&bookmarks_ids)?;
+
+    write_settings(settings)?;
+
+    remove_favicons(&merged);
 
-    let mut settings = get_settings();
-    settings.groups = new_groups;
+    send_notification("Merge duplicates", "Bookmarks merged successfully");
 
-    write_settings(settings)
+    Ok(())
 }
@@ -0,0 +1,128 @@
+use std::fmt;
+
+use crate::settings::migrations::SettingsError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Every way a command or result handler can fail. They are shown to the user as a
+/// notification or an error result instead of panicking
+#[derive(Debug)]
+pub enum Error {
+    MissingField(String),
+    MissingArg(String),
+    InvalidId(String),
+    BookmarkNotFound(usize),
+    GroupNotFound(usize),
+    GroupCycle(String),
+    InvalidKeyword(String),
+    KeywordTaken(String),
+    AliasTaken(String, String),
+    InvalidLaunchCommand(String),
+    DuplicateUrl(String),
+    NoRedirect(String),
+    UnknownCommand(String),
+    Settings(SettingsError),
+    Import(String),
+    InvalidUrl(String),
+    Io(std::io::Error),
+    Image(image::ImageError),
+    Request(reqwest::Error),
+    PageDetails(reqwest::Error),
+    Open(String, std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingField(field) => write!(f, "The form is missing the {} field", field),
+            Error::MissingArg(arg) => write!(f, "The command is missing the {} argument", arg),
+            Error::InvalidId(id) => write!(f, "{} is not a valid id", id),
+            Error::BookmarkNotFound(id) => write!(f, "Bookmark {} no longer exists", id),
+            Error::GroupNotFound(id) => write!(f, "Group {} no longer exists", id),
+            Error::GroupCycle(name) => write!(
+                f,
+                "{} can't contain a group that already contains it",
+                name
+            ),
+            Error::InvalidKeyword(keyword) => write!(
+                f,
+                "{} can't be used as a keyword, it must be a single word that isn't already an extension keyword",
+                keyword
+            ),
+            Error::KeywordTaken(keyword) => {
+                write!(f, "Another bookmark already uses the {} keyword", keyword)
+            }
+            Error::AliasTaken(alias, name) => {
+                write!(f, "The {} alias is already used by {}", alias, name)
+            }
+            Error::InvalidLaunchCommand(command) => {
+                write!(f, "Invalid launch command, check its quotes: {}", command)
+            }
+            Error::DuplicateUrl(name) => write!(
+                f,
+                "{} already points to this url, type duplicates to merge bookmarks",
+                name
+            ),
+            Error::NoRedirect(name) => write!(
+                f,
+                "{} doesn't permanently redirect anywhere, check the links again",
+                name
+            ),
+            Error::UnknownCommand(command) => write!(f, "Unknown command {}", command),
+            Error::Settings(error) => write!(f, "{}", error),
+            Error::Import(error) => write!(f, "{}", error),
+            Error::InvalidUrl(url) => write!(f, "{} is not a valid url", url),
+            Error::Io(error) => write!(f, "File error: {}", error),
+            Error::Image(error) => write!(f, "Error reading icon: {}", error),
+            Error::Request(error) => write!(
+                f,
+                "Error getting icon. Make sure you have a valid url and internet connection: {}",
+                error
+            ),
+            Error::PageDetails(error) => {
+                write!(f, "Couldn't read the page title and description: {}", error)
+            }
+            Error::Open(url, error) => write!(f, "Error opening {}: {}", url, error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<SettingsError> for Error {
+    fn from(error: SettingsError) -> Self {
+        Error::Settings(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(error: image::ImageError) -> Self {
+        Error::Image(error)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Error::Request(error)
+    }
+}
+
+/// Turns a missing form result into an error naming the field
+pub fn required_field<T>(value: Option<T>, field: &str) -> Result<T> {
+    value.ok_or_else(|| Error::MissingField(field.to_string()))
+}
+
+/// Parses the id passed as the first argument of a command or form
+pub fn get_id_arg(args: &[String]) -> Result<usize> {
+    let arg = args
+        .first()
+        .ok_or_else(|| Error::MissingArg("id".to_string()))?;
+
+    arg.parse().map_err(|_| Error::InvalidId(arg.to_owned()))
+}
@@ -0,0 +1,118 @@
+use std::{fs, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{
+    paths::get_favicons_dir,
+    settings::{Bookmark, Group, Settings},
+};
+
+/// Writes every bookmark as a Netscape `bookmarks.html` that any browser can import.
+/// Each group becomes a folder and bookmarks without a group stay at the top level
+pub fn to_netscape_html(settings: &Settings) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+        <!-- This is an automatically generated file.\n     It will be read and overwritten.\n     DO NOT EDIT! -->\n\
+        <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+        <TITLE>Bookmarks</TITLE>\n\
+        <H1>Bookmarks</H1>\n\
+        <DL><p>\n",
+    );
+
+    // Groups inside other groups are written inside their parent folder
+    for group in &settings.groups {
+        let is_child = settings
+            .groups
+            .iter()
+            .any(|g| g.groups_ids.contains(&group.id));
+
+        if !is_child {
+            write_group(&mut html, settings, group, 1, &mut Vec::new());
+        }
+    }
+
+    for bookmark in &settings.bookmarks {
+        let in_group = settings
+            .groups
+            .iter()
+            .any(|g| g.bookmarks_ids.contains(&bookmark.id));
+
+        if !in_group {
+            html.push_str(&format!("    {}\n", get_bookmark_entry(bookmark)));
+        }
+    }
+
+    html.push_str("</DL><p>\n");
+    html
+}
+
+fn write_group(
+    html: &mut String,
+    settings: &Settings,
+    group: &Group,
+    depth: usize,
+    visited: &mut Vec<usize>,
+) {
+    if visited.contains(&group.id) {
+        return;
+    }
+
+    visited.push(group.id);
+
+    let indent = "    ".repeat(depth);
+
+    html.push_str(&format!("{}<DT><H3>{}</H3>\n", indent, escape(&group.name)));
+    html.push_str(&format!("{}<DL><p>\n", indent));
+
+    for child_id in &group.groups_ids {
+        if let Some(child) = settings.groups.iter().find(|g| g.id == *child_id) {
+            write_group(html, settings, child, depth + 1, visited);
+        }
+    }
+
+    for bookmark_id in &group.bookmarks_ids {
+        if let Some(bookmark) = settings.bookmarks.iter().find(|b| b.id == *bookmark_id) {
+            html.push_str(&format!("{}    {}\n", indent, get_bookmark_entry(bookmark)));
+        }
+    }
+
+    html.push_str(&format!("{}</DL><p>\n", indent));
+}
+
+fn get_bookmark_entry(bookmark: &Bookmark) -> String {
+    let mut attributes = format!("HREF=\"{}\"", escape(&bookmark.url));
+
+    if let Some(created_at) = bookmark.created_at {
+        attributes.push_str(&format!(" ADD_DATE=\"{}\"", created_at));
+    }
+
+    if let Some(keyword) = &bookmark.keyword {
+        attributes.push_str(&format!(" SHORTCUTURL=\"{}\"", escape(keyword)));
+    }
+
+    if let Some(icon) = get_favicon_data_uri(bookmark) {
+        attributes.push_str(&format!(" ICON=\"{}\"", icon));
+    }
+
+    format!("<DT><A {}>{}</A>", attributes, escape(&bookmark.name))
+}
+
+/// Only icons downloaded by the extension are embedded, they are always png
+fn get_favicon_data_uri(bookmark: &Bookmark) -> Option<String> {
+    let icon_path = Path::new(bookmark.icon_path.as_deref()?);
+
+    if !icon_path.starts_with(get_favicons_dir()) {
+        return None;
+    }
+
+    let bytes = fs::read(icon_path).ok()?;
+
+    Some(format!("data:image/png;base64,{}", STANDARD.encode(bytes)))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
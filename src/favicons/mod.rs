@@ -0,0 +1,266 @@
+use std::{cmp::Reverse, fs, io::Cursor, path::Path, time::Duration};
+
+use image::{ImageFormat, ImageReader};
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+use whiskers_launcher_core::features::extensions::get_extension_setting;
+
+use crate::{
+    error::{Error, Result},
+    html::get_tags,
+    paths::get_favicons_dir,
+    settings::Bookmark,
+    urls::parse_url,
+    ID,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where website icons are downloaded from. Chosen with the `favicon-provider` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaviconProvider {
+    /// Reads the icons the website declares, without asking any third party
+    Direct,
+    Google,
+    DuckDuckGo,
+}
+
+#[derive(Deserialize)]
+struct WebManifest {
+    #[serde(default)]
+    icons: Vec<ManifestIcon>,
+}
+
+#[derive(Deserialize)]
+struct ManifestIcon {
+    src: String,
+    sizes: Option<String>,
+    r#type: Option<String>,
+}
+
+/// An icon url and its declared size, used to try the biggest ones first
+struct Candidate {
+    url: Url,
+    size: u32,
+}
+
+impl FaviconProvider {
+    pub fn from_setting() -> Self {
+        match get_extension_setting(ID, "favicon-provider").as_deref() {
+            Some("google") => FaviconProvider::Google,
+            Some("duckduckgo") => FaviconProvider::DuckDuckGo,
+            _ => FaviconProvider::Direct,
+        }
+    }
+
+    /// Returns the bytes of an icon that can be decoded
+    pub async fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        let client = get_client()?;
+        let page_url = parse_url(url)?;
+        let host = page_url
+            .host_str()
+            .ok_or_else(|| Error::InvalidUrl(url.to_string()))?;
+
+        match self {
+            FaviconProvider::Direct => fetch_direct(&client, &page_url).await,
+            FaviconProvider::Google => {
+                let url = format!("https://www.google.com/s2/favicons?domain={}&sz=256", host);
+                download_icon(&client, &url).await
+            }
+            FaviconProvider::DuckDuckGo => {
+                let url = format!("https://icons.duckduckgo.com/ip3/{}.ico", host);
+                download_icon(&client, &url).await
+            }
+        }
+    }
+}
+
+/// Downloads the website icon of `url` with the configured provider
+pub async fn fetch_favicon(bookmark_id: usize, url: &str) -> Result<String> {
+    let bytes = FaviconProvider::from_setting().fetch(url).await?;
+    save_favicon(bookmark_id, &bytes)
+}
+
+/// Downloads an image, like a page `og:image`, and uses it as the bookmark icon
+pub async fn fetch_image_icon(bookmark_id: usize, image_url: &str) -> Result<String> {
+    let bytes = download_icon(&get_client()?, image_url).await?;
+    save_favicon(bookmark_id, &bytes)
+}
+
+/// Decodes an icon in any supported format and stores it as `favicons/{bookmark_id}.png`
+pub fn save_favicon(bookmark_id: usize, bytes: &[u8]) -> Result<String> {
+    let mut path = get_favicons_dir();
+
+    fs::create_dir_all(&path)?;
+
+    path.push(format!("{}.png", bookmark_id));
+
+    let image = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .decode()?;
+
+    image.save_with_format(&path, ImageFormat::Png)?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Deletes the downloaded icons of bookmarks that were deleted for good. Icons outside the
+/// favicons folder belong to the user and are left alone
+pub fn remove_favicons<'a>(bookmarks: impl IntoIterator<Item = &'a Bookmark>) {
+    let favicons_dir = get_favicons_dir();
+
+    for icon_path in bookmarks.into_iter().filter_map(|b| b.icon_path.as_ref()) {
+        let icon_path = Path::new(icon_path);
+
+        // A leftover file is harmless, so it isn't worth failing the command over
+        if icon_path.starts_with(&favicons_dir) {
+            let _ = fs::remove_file(icon_path);
+        }
+    }
+}
+
+pub fn get_client() -> Result<Client> {
+    Ok(Client::builder()
+        .timeout(TIMEOUT)
+        .user_agent(concat!(
+            "whiskers-launcher-bookmarks/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()?)
+}
+
+/// Tries `<link rel=icon>`, apple-touch-icon and web app manifest icons, biggest first,
+/// then falls back to `/favicon.ico`
+async fn fetch_direct(client: &Client, page_url: &Url) -> Result<Vec<u8>> {
+    let mut candidates = Vec::<Candidate>::new();
+
+    // An unreachable page can still serve /favicon.ico
+    if let Ok(response) = client.get(page_url.as_str()).send().await {
+        let base_url = response.url().to_owned();
+
+        if let Ok(html) = response.text().await {
+            candidates = get_page_candidates(client, &base_url, &html).await;
+        }
+    }
+
+    candidates.sort_by_key(|candidate| Reverse(candidate.size));
+
+    if let Ok(url) = page_url.join("/favicon.ico") {
+        candidates.push(Candidate { url, size: 0 });
+    }
+
+    let mut last_error = Error::InvalidUrl(page_url.to_string());
+
+    for candidate in candidates {
+        match download_icon(client, candidate.url.as_str()).await {
+            Ok(bytes) => return Ok(bytes),
+            Err(error) => last_error = error,
+        }
+    }
+
+    Err(last_error)
+}
+
+async fn get_page_candidates(client: &Client, base_url: &Url, html: &str) -> Vec<Candidate> {
+    let mut candidates = Vec::<Candidate>::new();
+    let mut manifest_url: Option<Url> = None;
+
+    for tag in get_tags(html) {
+        if tag.name == "/head" || tag.name == "body" {
+            break;
+        }
+
+        if tag.name != "link" {
+            continue;
+        }
+
+        let rel = tag.get_attribute("rel").unwrap_or_default().to_lowercase();
+        let href = match tag
+            .get_attribute("href")
+            .and_then(|h| base_url.join(&h).ok())
+        {
+            Some(href) => href,
+            None => continue,
+        };
+
+        let rels: Vec<&str> = rel.split_whitespace().collect();
+
+        if rels.contains(&"manifest") {
+            manifest_url = Some(href);
+        } else if rels.contains(&"icon") || rels.contains(&"apple-touch-icon") {
+            // SVG icons can't be decoded
+            if is_svg(href.path(), tag.get_attribute("type").as_deref()) {
+                continue;
+            }
+
+            let default_size = if rels.contains(&"apple-touch-icon") {
+                180
+            } else {
+                16
+            };
+
+            candidates.push(Candidate {
+                url: href,
+                size: get_size(tag.get_attribute("sizes").as_deref()).unwrap_or(default_size),
+            });
+        }
+    }
+
+    if let Some(manifest_url) = manifest_url {
+        candidates.extend(get_manifest_candidates(client, &manifest_url).await);
+    }
+
+    candidates
+}
+
+async fn get_manifest_candidates(client: &Client, manifest_url: &Url) -> Vec<Candidate> {
+    let bytes = match client.get(manifest_url.as_str()).send().await {
+        Ok(response) => response.bytes().await.unwrap_or_default(),
+        Err(_) => return Vec::new(),
+    };
+
+    let manifest: WebManifest = match serde_json::from_slice(&bytes) {
+        Ok(manifest) => manifest,
+        Err(_) => return Vec::new(),
+    };
+
+    manifest
+        .icons
+        .into_iter()
+        .filter(|icon| !is_svg(&icon.src, icon.r#type.as_deref()))
+        .filter_map(|icon| {
+            Some(Candidate {
+                url: manifest_url.join(&icon.src).ok()?,
+                size: get_size(icon.sizes.as_deref()).unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Downloads an icon and makes sure it can be decoded
+async fn download_icon(client: &Client, url: &str) -> Result<Vec<u8>> {
+    let bytes = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    image::load_from_memory(&bytes)?;
+
+    Ok(bytes.to_vec())
+}
+
+/// Reads the biggest size of `sizes="16x16 32x32"`
+fn get_size(sizes: Option<&str>) -> Option<u32> {
+    sizes?
+        .split_whitespace()
+        .filter_map(|size| size.to_lowercase().split('x').next()?.parse().ok())
+        .max()
+}
+
+fn is_svg(path: &str, mime_type: Option<&str>) -> bool {
+    path.ends_with(".svg") || mime_type.is_some_and(|t| t.contains("svg"))
+}
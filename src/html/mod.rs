@@ -0,0 +1,99 @@
+/// A start or end tag found by `get_tags`. `text` is what follows it up to the next tag
+#[derive(Debug, Clone)]
+pub struct Tag<'a> {
+    pub name: String,
+    pub attributes: &'a str,
+    pub text: &'a str,
+}
+
+impl Tag<'_> {
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        get_attribute(self.attributes, name)
+    }
+}
+
+/// Splits html into tags without building a tree. Good enough for bookmark exports and page
+/// heads, which are often not valid html anyway
+pub fn get_tags(html: &str) -> Vec<Tag<'_>> {
+    let mut tags = Vec::<Tag>::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+
+        let attributes = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let name = attributes
+            .split(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_ascii_lowercase();
+
+        let text = match rest.find('<') {
+            Some(end) => &rest[..end],
+            None => rest,
+        };
+
+        tags.push(Tag {
+            name,
+            attributes,
+            text,
+        });
+    }
+
+    tags
+}
+
+/// Reads `NAME="value"` pairs, ignoring the attribute name case
+pub fn get_attribute(tag: &str, name: &str) -> Option<String> {
+    let lower_tag = tag.to_ascii_lowercase();
+    let mut search_from = 0;
+
+    while let Some(position) = lower_tag[search_from..].find(name) {
+        let start = search_from + position;
+        search_from = start + name.len();
+
+        let is_word_start = start == 0
+            || lower_tag[..start]
+                .chars()
+                .last()
+                .is_some_and(|c| c.is_whitespace());
+
+        let after_name = lower_tag[search_from..].trim_start();
+
+        if !is_word_start || !after_name.starts_with('=') {
+            continue;
+        }
+
+        let value_start = tag.len() - after_name.len() + 1;
+        let value = tag[value_start..].trim_start();
+
+        let value = match value.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                let value = &value[1..];
+                &value[..value.find(quote).unwrap_or(value.len())]
+            }
+            _ => value.split_whitespace().next().unwrap_or_default(),
+        };
+
+        return Some(decode_entities(value));
+    }
+
+    None
+}
+
+pub fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+}
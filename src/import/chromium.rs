@@ -0,0 +1,140 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use serde::Deserialize;
+
+use super::{ImportTree, ImportedBookmark};
+
+/// Seconds between 1601-01-01, the epoch chromium uses, and the unix epoch
+const WINDOWS_EPOCH_OFFSET: u64 = 11_644_473_600;
+
+/// Config directories of chromium based browsers and the name shown for them
+const BROWSERS: [(&str, &str); 6] = [
+    ("google-chrome", "Chrome"),
+    ("google-chrome-beta", "Chrome Beta"),
+    ("chromium", "Chromium"),
+    ("BraveSoftware/Brave-Browser", "Brave"),
+    ("microsoft-edge", "Edge"),
+    ("vivaldi", "Vivaldi"),
+];
+
+#[derive(Deserialize)]
+struct BookmarksFile {
+    roots: HashMap<String, Node>,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    r#type: String,
+    url: Option<String>,
+    date_added: Option<String>,
+    #[serde(default)]
+    children: Vec<Node>,
+}
+
+/// A `Bookmarks` file found in a browser profile
+#[derive(Debug, Clone)]
+pub struct BrowserProfile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+pub fn is_bookmarks_file(text: &str) -> bool {
+    text.trim_start().starts_with('{') && text.contains("\"roots\"")
+}
+
+/// Looks for `{config}/{browser}/{profile}/Bookmarks` in the usual linux config paths
+pub fn find_profiles() -> Vec<BrowserProfile> {
+    let config_dir = match dirs::config_dir() {
+        Some(dir) => dir,
+        None => return Vec::new(),
+    };
+
+    let mut profiles = Vec::<BrowserProfile>::new();
+
+    for (dir, browser) in BROWSERS {
+        let entries = match fs::read_dir(config_dir.join(dir)) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        let mut browser_profiles: Vec<BrowserProfile> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join("Bookmarks"))
+            .filter(|path| path.is_file())
+            .map(|path| {
+                let profile = path
+                    .parent()
+                    .and_then(|p| p.file_name())
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                BrowserProfile {
+                    name: format!("{} ({})", browser, profile),
+                    path,
+                }
+            })
+            .collect();
+
+        browser_profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        profiles.extend(browser_profiles);
+    }
+
+    profiles
+}
+
+pub fn parse(bytes: &[u8]) -> Result<ImportTree, String> {
+    let file: BookmarksFile =
+        serde_json::from_slice(bytes).map_err(|e| format!("Error reading bookmarks: {}", e))?;
+
+    let mut tree = ImportTree::default();
+
+    // Keep the order the browser shows them in
+    for root in ["bookmark_bar", "other", "synced"] {
+        if let Some(node) = file.roots.get(root) {
+            add_node(&mut tree, node, None);
+        }
+    }
+
+    Ok(tree)
+}
+
+fn add_node(tree: &mut ImportTree, node: &Node, folder: Option<usize>) {
+    match node.r#type.as_str() {
+        "folder" => {
+            let folder = tree.add_folder(&node.name, folder);
+
+            for child in &node.children {
+                add_node(tree, child, Some(folder));
+            }
+        }
+        "url" => {
+            let url = match &node.url {
+                Some(url) if !url.starts_with("javascript:") => url.to_owned(),
+                _ => return,
+            };
+
+            let bookmark = ImportedBookmark {
+                name: if node.name.is_empty() {
+                    url.to_owned()
+                } else {
+                    node.name.to_owned()
+                },
+                created_at: node.date_added.as_deref().and_then(to_unix_timestamp),
+                url,
+                ..Default::default()
+            };
+
+            tree.add_bookmark(bookmark, folder);
+        }
+        _ => {}
+    }
+}
+
+/// Chromium stores microseconds since 1601-01-01 as a string
+fn to_unix_timestamp(date: &str) -> Option<u64> {
+    let seconds = date.parse::<u64>().ok()? / 1_000_000;
+    seconds.checked_sub(WINDOWS_EPOCH_OFFSET)
+}
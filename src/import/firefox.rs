@@ -0,0 +1,376 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde::Deserialize;
+
+use super::{ImportTree, ImportedBookmark};
+
+const PLACES_FILE: &str = "places.sqlite";
+const FAVICONS_FILE: &str = "favicons.sqlite";
+const MOZ_LZ4_MAGIC: &[u8; 8] = b"mozLz40\0";
+const PLACES_ROOT_GUID: &str = "root________";
+const TAGS_ROOT_GUID: &str = "tags________";
+
+pub fn is_moz_lz4(bytes: &[u8]) -> bool {
+    bytes.starts_with(MOZ_LZ4_MAGIC)
+}
+
+/// Imports from a profile directory, preferring `places.sqlite` over the newest backup
+pub fn read_profile(profile_dir: &Path) -> Result<ImportTree, String> {
+    let places_path = profile_dir.join(PLACES_FILE);
+
+    if places_path.exists() {
+        return read_places(&places_path);
+    }
+
+    let backups_dir = profile_dir.join("bookmarkbackups");
+    let newest_backup = fs::read_dir(&backups_dir)
+        .map_err(|_| "The selected directory isn't a Firefox profile".to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "jsonlz4"))
+        .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok());
+
+    match newest_backup {
+        Some(path) => read_backup(&path),
+        None => Err("No bookmark backups found in the Firefox profile".to_string()),
+    }
+}
+
+/// Reads the bookmark tree of a `places.sqlite` file. Firefox keeps the database locked while
+/// running, so a copy is opened instead of the original
+pub fn read_places(path: &Path) -> Result<ImportTree, String> {
+    let tree = read_places_copy(path);
+    remove_copies();
+    tree
+}
+
+fn read_places_copy(path: &Path) -> Result<ImportTree, String> {
+    let connection = open_copy(path)?;
+    let favicons = Favicons::open(path.parent());
+    let sql_error = |e: rusqlite::Error| format!("Error reading places.sqlite: {}", e);
+
+    let mut statement = connection
+        .prepare("SELECT id, parent, title, guid FROM moz_bookmarks WHERE type = 2")
+        .map_err(sql_error)?;
+
+    let folders: Vec<(i64, i64, Option<String>, String)> = statement
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(sql_error)?
+        .filter_map(|row| row.ok())
+        .collect();
+
+    let tags_root = folders
+        .iter()
+        .find(|(_, _, _, guid)| guid == TAGS_ROOT_GUID)
+        .map(|(id, _, _, _)| *id);
+
+    let tag_folders: HashMap<i64, String> = folders
+        .iter()
+        .filter(|(_, parent, _, _)| Some(*parent) == tags_root)
+        .map(|(id, _, title, _)| (*id, title.to_owned().unwrap_or_default()))
+        .collect();
+
+    let mut tree = ImportTree::default();
+    let mut folder_indexes = HashMap::<i64, usize>::new();
+
+    for (id, _, title, guid) in &folders {
+        if guid == PLACES_ROOT_GUID || Some(*id) == tags_root || tag_folders.contains_key(id) {
+            continue;
+        }
+
+        let name = get_root_name(guid)
+            .map(|n| n.to_string())
+            .or(title.to_owned())
+            .unwrap_or_default();
+
+        folder_indexes.insert(*id, tree.add_folder(name, None));
+    }
+
+    // Parents can have a bigger id than their children, so they are linked once all exist
+    for (id, parent, _, _) in &folders {
+        if let (Some(index), Some(parent_index)) =
+            (folder_indexes.get(id), folder_indexes.get(parent))
+        {
+            tree.folders[*parent_index].folders.push(*index);
+        }
+    }
+
+    let mut statement = connection
+        .prepare(
+            "SELECT b.parent, b.title, b.dateAdded, p.url, p.title, k.keyword
+            FROM moz_bookmarks b
+            JOIN moz_places p ON p.id = b.fk
+            LEFT JOIN moz_keywords k ON k.place_id = p.id
+            WHERE b.type = 1",
+        )
+        .map_err(sql_error)?;
+
+    #[allow(clippy::type_complexity)]
+    let rows: Vec<(
+        i64,
+        Option<String>,
+        Option<i64>,
+        String,
+        Option<String>,
+        Option<String>,
+    )> = statement
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })
+        .map_err(sql_error)?
+        .filter_map(|row| row.ok())
+        .collect();
+
+    // Tags are stored as bookmarks of the same url inside the tag folders
+    let mut tags = HashMap::<String, Vec<String>>::new();
+
+    for (parent, _, _, url, _, _) in &rows {
+        if let Some(tag) = tag_folders.get(parent) {
+            tags.entry(url.to_owned()).or_default().push(tag.to_owned());
+        }
+    }
+
+    for (parent, title, date_added, url, page_title, keyword) in rows {
+        if tag_folders.contains_key(&parent) || !is_importable(&url) {
+            continue;
+        }
+
+        let bookmark = ImportedBookmark {
+            name: title
+                .filter(|t| !t.is_empty())
+                .or(page_title)
+                .unwrap_or(url.to_owned()),
+            created_at: date_added.map(|d| (d / 1_000_000) as u64),
+            icon: favicons.get(&url),
+            keyword,
+            tags: tags.get(&url).cloned().unwrap_or_default(),
+            url,
+        };
+
+        tree.add_bookmark(bookmark, folder_indexes.get(&parent).copied());
+    }
+
+    Ok(tree)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupNode {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    r#type: String,
+    root: Option<String>,
+    uri: Option<String>,
+    date_added: Option<u64>,
+    keyword: Option<String>,
+    tags: Option<String>,
+    #[serde(default)]
+    children: Vec<BackupNode>,
+}
+
+/// Reads a `bookmarkbackups/*.jsonlz4` file
+pub fn read_backup(path: &Path) -> Result<ImportTree, String> {
+    let tree = read_backup_file(path);
+    remove_copies();
+    tree
+}
+
+fn read_backup_file(path: &Path) -> Result<ImportTree, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    let json = decompress_moz_lz4(&bytes)?;
+    let root: BackupNode =
+        serde_json::from_slice(&json).map_err(|e| format!("Error reading backup: {}", e))?;
+
+    // Backups live in `{profile}/bookmarkbackups`
+    let favicons = Favicons::open(path.parent().and_then(|p| p.parent()));
+    let mut tree = ImportTree::default();
+
+    add_backup_node(&mut tree, &favicons, &root, None);
+
+    Ok(tree)
+}
+
+fn add_backup_node(
+    tree: &mut ImportTree,
+    favicons: &Favicons,
+    node: &BackupNode,
+    folder: Option<usize>,
+) {
+    match node.r#type.as_str() {
+        "text/x-moz-place-container" => {
+            let root = node.root.as_deref();
+
+            // Tags are already listed on each bookmark
+            if root == Some("tagsFolder") {
+                return;
+            }
+
+            let folder = match root {
+                Some("placesRoot") => None,
+                _ => {
+                    let name = get_backup_root_name(root).unwrap_or(&node.title);
+                    Some(tree.add_folder(name, folder))
+                }
+            };
+
+            for child in &node.children {
+                add_backup_node(tree, favicons, child, folder);
+            }
+        }
+        "text/x-moz-place" => {
+            let url = match &node.uri {
+                Some(url) if is_importable(url) => url.to_owned(),
+                _ => return,
+            };
+
+            let bookmark = ImportedBookmark {
+                name: if node.title.is_empty() {
+                    url.to_owned()
+                } else {
+                    node.title.to_owned()
+                },
+                created_at: node.date_added.map(|d| d / 1_000_000),
+                icon: favicons.get(&url),
+                keyword: node.keyword.to_owned(),
+                tags: node
+                    .tags
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect(),
+                url,
+            };
+
+            tree.add_bookmark(bookmark, folder);
+        }
+        _ => {}
+    }
+}
+
+/// `mozLz40\0`, the decompressed size as a little endian u32 and a raw lz4 block
+fn decompress_moz_lz4(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    if !is_moz_lz4(bytes) || bytes.len() < 12 {
+        return Err("Invalid jsonlz4 file".to_string());
+    }
+
+    let size = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+
+    lz4_flex::block::decompress(&bytes[12..], size)
+        .map_err(|e| format!("Error decompressing backup: {}", e))
+}
+
+fn open_copy(path: &Path) -> Result<Connection, String> {
+    let copy_path = copy_database(path)?;
+
+    Connection::open_with_flags(&copy_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Error opening {}: {}", path.display(), e))
+}
+
+fn get_copies_dir() -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!(
+        "whiskers-launcher-bookmarks-{}",
+        std::process::id()
+    ));
+    path
+}
+
+fn remove_copies() {
+    let _ = fs::remove_dir_all(get_copies_dir());
+}
+
+/// Copies the database and its write ahead log to the temporary directory
+fn copy_database(path: &Path) -> Result<PathBuf, String> {
+    let copy_dir = get_copies_dir();
+
+    fs::create_dir_all(&copy_dir).map_err(|e| format!("Error creating temp dir: {}", e))?;
+
+    let file_name = path.file_name().ok_or("Invalid database path")?;
+    let copy_path = copy_dir.join(file_name);
+
+    fs::copy(path, &copy_path).map_err(|e| format!("Error copying {}: {}", path.display(), e))?;
+
+    let mut wal_name = file_name.to_owned();
+    wal_name.push("-wal");
+    let wal_path = path.with_file_name(&wal_name);
+
+    if wal_path.exists() {
+        let _ = fs::copy(&wal_path, copy_dir.join(&wal_name));
+    }
+
+    Ok(copy_path)
+}
+
+/// Icons Firefox already downloaded, read from the profile `favicons.sqlite`
+struct Favicons {
+    connection: Option<Connection>,
+}
+
+impl Favicons {
+    fn open(profile_dir: Option<&Path>) -> Self {
+        let connection = profile_dir
+            .map(|dir| dir.join(FAVICONS_FILE))
+            .filter(|path| path.exists())
+            .and_then(|path| open_copy(&path).ok());
+
+        Self { connection }
+    }
+
+    fn get(&self, url: &str) -> Option<Vec<u8>> {
+        self.connection
+            .as_ref()?
+            .query_row(
+                "SELECT i.data FROM moz_icons i
+                JOIN moz_icons_to_pages ip ON ip.icon_id = i.id
+                JOIN moz_pages_w_icons p ON p.id = ip.page_id
+                WHERE p.page_url = ?1
+                ORDER BY i.width DESC LIMIT 1",
+                [url],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten()
+    }
+}
+
+fn is_importable(url: &str) -> bool {
+    !url.starts_with("place:") && !url.starts_with("javascript:")
+}
+
+fn get_root_name(guid: &str) -> Option<&'static str> {
+    match guid {
+        "menu________" => Some("Bookmarks Menu"),
+        "toolbar_____" => Some("Bookmarks Toolbar"),
+        "unfiled_____" => Some("Other Bookmarks"),
+        "mobile______" => Some("Mobile Bookmarks"),
+        _ => None,
+    }
+}
+
+fn get_backup_root_name(root: Option<&str>) -> Option<&'static str> {
+    match root? {
+        "bookmarksMenuFolder" => Some("Bookmarks Menu"),
+        "toolbarFolder" => Some("Bookmarks Toolbar"),
+        "unfiledBookmarksFolder" => Some("Other Bookmarks"),
+        "mobileFolder" => Some("Mobile Bookmarks"),
+        _ => None,
+    }
+}
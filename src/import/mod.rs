@@ -0,0 +1,218 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{
+    favicons::save_favicon,
+    settings::{parse_keyword, Bookmark, Group, Settings},
+    urls::{canonicalize_url, normalize_url},
+};
+
+pub mod chromium;
+pub mod firefox;
+pub mod netscape;
+
+const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+/// A bookmark read from a browser, before it gets an id
+#[derive(Debug, Clone, Default)]
+pub struct ImportedBookmark {
+    pub name: String,
+    pub url: String,
+    pub created_at: Option<u64>,
+    pub icon: Option<Vec<u8>>,
+    pub keyword: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// A browser folder. `bookmarks` and `folders` are indexes into the `ImportTree` lists
+#[derive(Debug, Clone, Default)]
+pub struct ImportedFolder {
+    pub name: String,
+    pub bookmarks: Vec<usize>,
+    pub folders: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportTree {
+    pub bookmarks: Vec<ImportedBookmark>,
+    pub folders: Vec<ImportedFolder>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportSummary {
+    pub bookmarks: usize,
+    pub groups: usize,
+    /// Bookmarks skipped because their url was already saved
+    pub duplicates: usize,
+}
+
+impl ImportTree {
+    pub fn add_folder(&mut self, name: impl Into<String>, parent: Option<usize>) -> usize {
+        self.folders.push(ImportedFolder {
+            name: name.into(),
+            ..Default::default()
+        });
+
+        let index = self.folders.len() - 1;
+
+        if let Some(parent) = parent {
+            self.folders[parent].folders.push(index);
+        }
+
+        index
+    }
+
+    /// Folders without bookmarks anywhere inside them don't become groups
+    fn has_bookmarks(&self, folder: usize) -> bool {
+        let folder = &self.folders[folder];
+
+        !folder.bookmarks.is_empty() || folder.folders.iter().any(|f| self.has_bookmarks(*f))
+    }
+
+    pub fn add_bookmark(&mut self, bookmark: ImportedBookmark, folder: Option<usize>) -> usize {
+        self.bookmarks.push(bookmark);
+        let index = self.bookmarks.len() - 1;
+
+        if let Some(folder) = folder {
+            self.folders[folder].bookmarks.push(index);
+        }
+
+        index
+    }
+}
+
+/// Picks the importer from the file contents. A directory is read as a Firefox profile
+pub fn read_import_file(path: &Path) -> Result<ImportTree, String> {
+    if path.is_dir() {
+        return firefox::read_profile(path);
+    }
+
+    let bytes = fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    if bytes.starts_with(SQLITE_MAGIC) {
+        return firefox::read_places(path);
+    }
+
+    if firefox::is_moz_lz4(&bytes) {
+        return firefox::read_backup(path);
+    }
+
+    let text = String::from_utf8_lossy(&bytes);
+
+    if chromium::is_bookmarks_file(&text) {
+        return chromium::parse(&bytes);
+    }
+
+    if text.contains("NETSCAPE-Bookmark-file") || text.to_lowercase().contains("<dl") {
+        return Ok(netscape::parse(&text));
+    }
+
+    Err("Unsupported bookmarks file".to_string())
+}
+
+/// Adds every bookmark of the tree to the settings and turns each non empty folder into a group.
+/// Subfolders become groups inside the group of their parent
+pub fn apply_import(settings: &mut Settings, tree: ImportTree) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    let mut ids = Vec::<usize>::new();
+
+    let kept_folders: Vec<bool> = (0..tree.folders.len())
+        .map(|f| tree.has_bookmarks(f))
+        .collect();
+
+    // Normalized url of every saved bookmark, so each one is only parsed once
+    let mut saved_urls = HashMap::<String, usize>::new();
+
+    for bookmark in &settings.bookmarks {
+        saved_urls
+            .entry(normalize_url(&bookmark.url))
+            .or_insert(bookmark.id);
+    }
+
+    for mut imported in tree.bookmarks {
+        let id = settings.next_bookmark_id();
+
+        // Urls the launcher can't open, like bookmarklets, are kept as they were
+        if let Ok(url) = canonicalize_url(&imported.url) {
+            imported.url = url;
+        }
+
+        let key = normalize_url(&imported.url);
+
+        // Folders still get the bookmark that was already saved
+        if let Some(duplicate_id) = saved_urls.get(&key) {
+            ids.push(*duplicate_id);
+            summary.duplicates += 1;
+            continue;
+        }
+
+        saved_urls.insert(key, id);
+
+        let mut bookmark = Bookmark::new(id, &imported.name, &imported.url).set_tags(imported.tags);
+        bookmark.created_at = imported.created_at;
+
+        // Keywords already used here or reserved by the extension are dropped
+        if let Some(keyword) = imported.keyword.as_deref().and_then(parse_keyword) {
+            if settings.validate_keyword(id, &keyword).is_ok() {
+                bookmark.keyword = Some(keyword);
+            }
+        }
+
+        if let Some(icon) = imported.icon {
+            if let Ok(path) = save_favicon(id, &icon) {
+                bookmark = bookmark.set_icon_path(path);
+            }
+        }
+
+        settings.bookmarks.push(bookmark);
+        ids.push(id);
+        summary.bookmarks += 1;
+    }
+
+    let first_group_id = settings.next_group_id();
+    let mut groups_ids = Vec::<Option<usize>>::new();
+
+    for kept in &kept_folders {
+        let id = first_group_id + groups_ids.iter().flatten().count();
+        groups_ids.push(if *kept { Some(id) } else { None });
+    }
+
+    for (index, folder) in tree.folders.into_iter().enumerate() {
+        let group_id = match groups_ids[index] {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let mut bookmarks_ids = Vec::<usize>::new();
+
+        for id in folder.bookmarks.iter().map(|i| ids[*i]) {
+            if !bookmarks_ids.contains(&id) {
+                bookmarks_ids.push(id);
+            }
+        }
+
+        let children_ids = folder
+            .folders
+            .iter()
+            .filter_map(|f| groups_ids[*f])
+            .collect();
+        let group = Group::new(group_id, folder.name, bookmarks_ids).set_groups_ids(children_ids);
+
+        settings.groups.push(group);
+        summary.groups += 1;
+    }
+
+    summary
+}
+
+/// Decodes `data:image/...;base64,...` icons as exported by browsers
+pub fn decode_data_uri(uri: &str) -> Option<Vec<u8>> {
+    let (header, data) = uri.strip_prefix("data:")?.split_once(',')?;
+
+    if !header.ends_with(";base64") {
+        return None;
+    }
+
+    STANDARD.decode(data.trim()).ok()
+}
@@ -0,0 +1,63 @@
+use crate::html::{decode_entities, get_tags};
+
+use super::{decode_data_uri, ImportTree, ImportedBookmark};
+
+/// Parses the Netscape `bookmarks.html` format every browser can export.
+/// The format is not valid HTML (`<DT>` and `<p>` are never closed), so it is read tag by tag
+pub fn parse(html: &str) -> ImportTree {
+    let mut tree = ImportTree::default();
+    let mut folders_stack = Vec::<Option<usize>>::new();
+    let mut pending_folder: Option<usize> = None;
+
+    for tag in get_tags(html) {
+        match tag.name.as_str() {
+            "h3" => {
+                let name = decode_entities(tag.text);
+                let parent = folders_stack.last().copied().flatten();
+                pending_folder = Some(tree.add_folder(name.trim(), parent));
+            }
+            "dl" => folders_stack.push(pending_folder.take()),
+            "/dl" => {
+                folders_stack.pop();
+            }
+            "a" => {
+                let url = match tag.get_attribute("href") {
+                    Some(url) => url,
+                    None => continue,
+                };
+
+                // Bookmarklets and browser internal pages can't be opened from the launcher
+                if url.starts_with("javascript:") || url.starts_with("place:") {
+                    continue;
+                }
+
+                let name = decode_entities(tag.text);
+                let bookmark = ImportedBookmark {
+                    name: if name.trim().is_empty() {
+                        url.to_owned()
+                    } else {
+                        name.trim().to_owned()
+                    },
+                    url,
+                    created_at: tag.get_attribute("add_date").and_then(|d| d.parse().ok()),
+                    icon: tag.get_attribute("icon").and_then(|i| decode_data_uri(&i)),
+                    keyword: tag.get_attribute("shortcuturl"),
+                    tags: tag
+                        .get_attribute("tags")
+                        .map(|tags| {
+                            tags.split(',')
+                                .map(|t| t.trim().to_string())
+                                .filter(|t| !t.is_empty())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                };
+
+                tree.add_bookmark(bookmark, folders_stack.last().copied().flatten());
+            }
+            _ => {}
+        }
+    }
+
+    tree
+}
@@ -0,0 +1,98 @@
+use std::process::{Command, Stdio};
+
+use crate::{
+    error::{Error, Result},
+    settings::encode_query,
+};
+
+/// Opens `url` with the launch command if there's one, like `firefox --private-window {url}`,
+/// or with the default browser otherwise
+pub fn open_url(url: &str, launch_command: Option<&str>) -> Result<()> {
+    let launch_command = match launch_command {
+        Some(launch_command) => launch_command,
+        None => return open::that_detached(url).map_err(|e| Error::Open(url.to_string(), e)),
+    };
+
+    let args = get_launch_args(launch_command, url)?;
+
+    Command::new(&args[0])
+        .args(&args[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| Error::Open(url.to_string(), e))?;
+
+    Ok(())
+}
+
+/// Checks that a launch command can be split into a program and its arguments
+pub fn validate_launch_command(launch_command: &str) -> Result<()> {
+    get_launch_args(launch_command, "").map(|_| ())
+}
+
+/// Splits the command like a shell would and fills the `{url}` and `{url-encoded}`
+/// placeholders. The url is added at the end when there are none
+fn get_launch_args(launch_command: &str, url: &str) -> Result<Vec<String>> {
+    let invalid = || Error::InvalidLaunchCommand(launch_command.to_string());
+    let mut args = split_command(launch_command).ok_or_else(invalid)?;
+
+    if args.is_empty() {
+        return Err(invalid());
+    }
+
+    let has_placeholder = args
+        .iter()
+        .any(|arg| arg.contains("{url}") || arg.contains("{url-encoded}"));
+
+    if has_placeholder {
+        for arg in args.iter_mut() {
+            *arg = arg
+                .replace("{url-encoded}", &encode_query(url))
+                .replace("{url}", url);
+        }
+    } else {
+        args.push(url.to_string());
+    }
+
+    Ok(args)
+}
+
+/// Splits on whitespace outside of quotes. Returns `None` when a quote isn't closed
+fn split_command(command: &str) -> Option<Vec<String>> {
+    let mut args = Vec::<String>::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in command.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return None;
+    }
+
+    if in_arg {
+        args.push(current);
+    }
+
+    Some(args)
+}
@@ -0,0 +1,162 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::{header::LOCATION, redirect::Policy, Client, Method, Response, StatusCode};
+use tokio::task::JoinSet;
+use url::Url;
+
+use crate::{
+    error::Result,
+    settings::{Bookmark, LinkCheck},
+    urls::parse_url,
+    usage::get_now,
+};
+
+/// How many links are checked at the same time
+const WORKERS: usize = 8;
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REDIRECTS: usize = 10;
+
+/// Checks the url of every web bookmark and returns what was found for each bookmark id
+pub async fn check_links(bookmarks: &[Bookmark]) -> Result<Vec<(usize, LinkCheck)>> {
+    let client = get_client()?;
+    let queue: VecDeque<(usize, String)> = bookmarks
+        .iter()
+        .map(|bookmark| (bookmark.id, bookmark.get_url_for("")))
+        .filter(|(_, url)| is_web_url(url))
+        .collect();
+    let queue = Arc::new(Mutex::new(queue));
+    let mut workers = JoinSet::new();
+
+    for _ in 0..WORKERS {
+        let client = client.clone();
+        let queue = queue.clone();
+
+        workers.spawn(async move {
+            let mut checks = Vec::<(usize, LinkCheck)>::new();
+
+            loop {
+                let next = queue.lock().ok().and_then(|mut queue| queue.pop_front());
+
+                let (id, url) = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+
+                checks.push((id, check_link(&client, &url).await));
+            }
+
+            checks
+        });
+    }
+
+    let mut checks = Vec::<(usize, LinkCheck)>::new();
+
+    while let Some(worker_checks) = workers.join_next().await {
+        checks.extend(worker_checks.unwrap_or_default());
+    }
+
+    Ok(checks)
+}
+
+/// Follows redirects by hand so it can tell permanent ones apart
+pub async fn check_link(client: &Client, url: &str) -> LinkCheck {
+    let mut check = LinkCheck {
+        status: None,
+        error: None,
+        redirect_url: None,
+        permanent_redirect: false,
+        checked_at: get_now(),
+    };
+
+    let mut current_url = match parse_url(url) {
+        Ok(url) => url,
+        Err(error) => {
+            check.error = Some(error.to_string());
+            return check;
+        }
+    };
+
+    let mut permanent = true;
+
+    for _ in 0..MAX_REDIRECTS {
+        let response = match send(client, &current_url).await {
+            Ok(response) => response,
+            Err(error) => {
+                check.error = Some(get_error_reason(&error));
+                return check;
+            }
+        };
+
+        let status = response.status();
+
+        if !status.is_redirection() {
+            check.status = Some(status.as_u16());
+            return check;
+        }
+
+        let next_url = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| current_url.join(location).ok());
+
+        let next_url = match next_url {
+            Some(next_url) => next_url,
+            // A redirect without a location is what the server answered, so keep its status
+            None => {
+                check.status = Some(status.as_u16());
+                return check;
+            }
+        };
+
+        permanent &=
+            status == StatusCode::MOVED_PERMANENTLY || status == StatusCode::PERMANENT_REDIRECT;
+
+        check.redirect_url = Some(next_url.to_string());
+        check.permanent_redirect = permanent;
+        current_url = next_url;
+    }
+
+    check.error = Some("too many redirects".to_string());
+    check
+}
+
+/// Mail, file and other links have nothing to request
+fn is_web_url(url: &str) -> bool {
+    parse_url(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Sends a HEAD request, falling back to GET for servers that don't handle HEAD properly
+async fn send(client: &Client, url: &Url) -> reqwest::Result<Response> {
+    let head = client.request(Method::HEAD, url.as_str()).send().await;
+
+    match head {
+        Ok(response) if response.status().as_u16() < 400 => Ok(response),
+        _ => client.get(url.as_str()).send().await,
+    }
+}
+
+fn get_error_reason(error: &reqwest::Error) -> String {
+    if error.is_timeout() {
+        "timed out".to_string()
+    } else if error.is_connect() {
+        "couldn't connect".to_string()
+    } else {
+        error.to_string()
+    }
+}
+
+fn get_client() -> Result<Client> {
+    Ok(Client::builder()
+        .timeout(TIMEOUT)
+        .redirect(Policy::none())
+        .user_agent(concat!(
+            "whiskers-launcher-bookmarks/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()?)
+}
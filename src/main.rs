@@ -2,10 +2,21 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 pub mod commands;
+pub mod error;
+pub mod export;
+pub mod favicons;
+pub mod html;
 pub mod icons;
+pub mod import;
+pub mod launch;
+pub mod links;
+pub mod pages;
 pub mod paths;
+pub mod ranking;
 pub mod results;
 pub mod settings;
+pub mod urls;
+pub mod usage;
 
 
 use commands::on_run_commands;
@@ -0,0 +1,214 @@
+use url::Url;
+
+use crate::{
+    error::{Error, Result},
+    favicons::get_client,
+    html::{decode_entities, get_tags},
+    urls::parse_url,
+};
+
+/// What a page says about itself in its head
+#[derive(Debug, Clone, Default)]
+pub struct PageDetails {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// From `og:image`, used as the icon when the website has none
+    pub image_url: Option<Url>,
+}
+
+/// Downloads the page and reads its title, description and image
+pub async fn fetch_page_details(url: &str) -> Result<PageDetails> {
+    let response = get_client()?
+        .get(parse_url(url)?.as_str())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(Error::PageDetails)?;
+
+    let base_url = response.url().to_owned();
+    let html = response.text().await.map_err(Error::PageDetails)?;
+
+    Ok(get_page_details(&base_url, &html))
+}
+
+/// Open Graph values win over `<title>` and `<meta name=description>`, they are usually
+/// written for people and don't have the site name appended
+pub fn get_page_details(base_url: &Url, html: &str) -> PageDetails {
+    let mut title: Option<String> = None;
+    let mut og_title: Option<String> = None;
+    let mut description: Option<String> = None;
+    let mut og_description: Option<String> = None;
+    let mut image_url: Option<Url> = None;
+
+    for tag in get_tags(html) {
+        if tag.name == "/head" || tag.name == "body" {
+            break;
+        }
+
+        if tag.name == "title" && title.is_none() {
+            title = clean_text(&decode_entities(tag.text));
+            continue;
+        }
+
+        if tag.name != "meta" {
+            continue;
+        }
+
+        let property = tag
+            .get_attribute("property")
+            .or_else(|| tag.get_attribute("name"))
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let content = match tag.get_attribute("content").and_then(|c| clean_text(&c)) {
+            Some(content) => content,
+            None => continue,
+        };
+
+        match property.as_str() {
+            "og:title" => og_title = Some(content),
+            "og:description" => og_description = Some(content),
+            "description" => description = Some(content),
+            "og:image" => image_url = base_url.join(&content).ok(),
+            _ => {}
+        }
+    }
+
+    PageDetails {
+        title: og_title.or(title),
+        description: og_description.or(description),
+        image_url,
+    }
+}
+
+/// Collapses the whitespace pages put in their titles. Empty means there's none
+fn clean_text(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    Some(text).filter(|t| !t.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    fn get_base_url() -> Url {
+        Url::parse("https://example.com/blog/post").unwrap()
+    }
+
+    #[test]
+    fn prefers_open_graph_values() {
+        let html = r#"<html><head>
+            <title>Dashboards - Example</title>
+            <meta name="description" content="Plain description">
+            <meta property="og:title" content="Dashboards">
+            <meta property="og:description" content="Open Graph description">
+            </head><body></body></html>"#;
+
+        let details = get_page_details(&get_base_url(), html);
+
+        assert_eq!(details.title.as_deref(), Some("Dashboards"));
+        assert_eq!(
+            details.description.as_deref(),
+            Some("Open Graph description")
+        );
+    }
+
+    #[test]
+    fn falls_back_to_title_and_description() {
+        let html = r#"<head>
+            <title>Dashboards &amp; Reports</title>
+            <meta name="Description" content="Plain description">
+            </head>"#;
+
+        let details = get_page_details(&get_base_url(), html);
+
+        assert_eq!(details.title.as_deref(), Some("Dashboards & Reports"));
+        assert_eq!(details.description.as_deref(), Some("Plain description"));
+        assert!(details.image_url.is_none());
+    }
+
+    #[test]
+    fn resolves_relative_images() {
+        let html = r#"<head><meta property="og:image" content="../images/cover.png"></head>"#;
+
+        let details = get_page_details(&get_base_url(), html);
+
+        assert_eq!(
+            details.image_url.unwrap().as_str(),
+            "https://example.com/images/cover.png"
+        );
+    }
+
+    #[test]
+    fn cleans_whitespace() {
+        let html = "<head><title>\n    Dashboards\n    and   Reports\n</title>\
+            <meta name=\"description\" content=\"   \"></head>";
+
+        let details = get_page_details(&get_base_url(), html);
+
+        assert_eq!(details.title.as_deref(), Some("Dashboards and Reports"));
+        assert!(details.description.is_none());
+    }
+
+    #[test]
+    fn stops_at_the_body() {
+        let html = r#"<head></head><body><meta property="og:title" content="Comment"></body>"#;
+
+        assert!(get_page_details(&get_base_url(), html).title.is_none());
+    }
+
+    /// Answers a single request with `status` and `body`
+    async fn serve_once(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).await.unwrap();
+
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn fetches_from_a_server() {
+        let body =
+            r#"<head><title>Local</title><meta property="og:image" content="/icon.png"></head>"#;
+        let base_url = serve_once("200 OK", body).await;
+
+        let details = fetch_page_details(&format!("{}/page", base_url))
+            .await
+            .unwrap();
+
+        assert_eq!(details.title.as_deref(), Some("Local"));
+        assert_eq!(
+            details.image_url.unwrap().as_str(),
+            format!("{}/icon.png", base_url)
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_page_errors() {
+        let base_url = serve_once("404 Not Found", "").await;
+
+        let result = fetch_page_details(&format!("{}/missing", base_url)).await;
+
+        assert!(matches!(result, Err(Error::PageDetails(_))));
+    }
+}
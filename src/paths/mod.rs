@@ -12,6 +12,30 @@ pub fn get_settings_path() -> PathBuf {
     path
 }
 
+pub fn get_text_settings_path() -> PathBuf {
+    let mut path = get_config_dir();
+    path.push("settings.json");
+    path
+}
+
+pub fn get_settings_lock_path() -> PathBuf {
+    let mut path = get_config_dir();
+    path.push("settings.lock");
+    path
+}
+
+pub fn get_usage_path() -> PathBuf {
+    let mut path = get_config_dir();
+    path.push("usage.json");
+    path
+}
+
+pub fn get_usage_lock_path() -> PathBuf {
+    let mut path = get_config_dir();
+    path.push("usage.lock");
+    path
+}
+
 pub fn get_favicons_dir() -> PathBuf{
     let mut path = get_config_dir();
     path.push("favicons");
@@ -0,0 +1,118 @@
+use sniffer_rs::sniffer::Sniffer;
+
+use crate::{
+    settings::{Bookmark, Group},
+    urls::parse_url,
+};
+
+/// How much a match in each field counts, so a name match beats the same match elsewhere
+const NAME_WEIGHT: f64 = 1.0;
+const TAGS_WEIGHT: f64 = 0.6;
+const HOST_WEIGHT: f64 = 0.5;
+const PATH_WEIGHT: f64 = 0.3;
+const DESCRIPTION_WEIGHT: f64 = 0.25;
+
+/// Stays below the 100 points between exact, prefix and contains matches
+const MAX_USAGE_BONUS: f64 = 99.0;
+
+/// Best weighted match between the name, tags, url host, url path and description
+pub fn get_bookmark_match_quality(
+    sniffer: &Sniffer,
+    bookmark: &Bookmark,
+    search_text: &str,
+) -> Option<f64> {
+    let mut qualities = Vec::<f64>::new();
+
+    qualities
+        .extend(get_match_quality(sniffer, &bookmark.name, search_text).map(|q| q * NAME_WEIGHT));
+
+    // Only the name is matched fuzzily, urls and descriptions are long enough to match almost anything
+    for tag in &bookmark.tags {
+        qualities.extend(get_text_match_quality(tag, search_text).map(|q| q * TAGS_WEIGHT));
+    }
+
+    if let Ok(url) = parse_url(&bookmark.url) {
+        let host = url.host_str().unwrap_or_default();
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        let host_path = format!("{}{}", host, url.path());
+
+        qualities.extend(get_text_match_quality(host, search_text).map(|q| q * HOST_WEIGHT));
+        qualities.extend(get_text_match_quality(&host_path, search_text).map(|q| q * PATH_WEIGHT));
+    }
+
+    if let Some(description) = &bookmark.description {
+        qualities.extend(
+            get_text_match_quality(description, search_text).map(|q| q * DESCRIPTION_WEIGHT),
+        );
+    }
+
+    qualities.into_iter().max_by(|a, b| a.total_cmp(b))
+}
+
+/// Best weighted match between the name and description
+pub fn get_group_match_quality(sniffer: &Sniffer, group: &Group, search_text: &str) -> Option<f64> {
+    let mut qualities = Vec::<f64>::new();
+
+    qualities.extend(get_match_quality(sniffer, &group.name, search_text).map(|q| q * NAME_WEIGHT));
+
+    if let Some(description) = &group.description {
+        qualities.extend(
+            get_text_match_quality(description, search_text).map(|q| q * DESCRIPTION_WEIGHT),
+        );
+    }
+
+    qualities.into_iter().max_by(|a, b| a.total_cmp(b))
+}
+
+/// Points for how well a name matches, so exact and prefix matches beat fuzzy ones
+pub fn get_match_quality(sniffer: &Sniffer, name: &str, search_text: &str) -> Option<f64> {
+    if search_text.trim().is_empty() {
+        return Some(0.0);
+    }
+
+    if let Some(quality) = get_text_match_quality(name, search_text) {
+        return Some(quality);
+    }
+
+    if sniffer.matches(name, search_text) {
+        return Some(0.0);
+    }
+
+    None
+}
+
+/// Like `get_match_quality` without the fuzzy fallback
+fn get_text_match_quality(text: &str, search_text: &str) -> Option<f64> {
+    let lower_search = search_text.trim().to_lowercase();
+
+    if lower_search.is_empty() {
+        return None;
+    }
+
+    let lower_text = text.to_lowercase();
+
+    if lower_text == lower_search {
+        return Some(300.0);
+    }
+
+    if lower_text.starts_with(&lower_search) {
+        return Some(200.0);
+    }
+
+    if lower_text.contains(&lower_search) {
+        return Some(100.0);
+    }
+
+    None
+}
+
+/// Combines match quality with frecency. Frecency grows logarithmically so a heavily used
+/// bookmark can climb above a slightly better match, but never above an exact one
+pub fn get_score(match_quality: f64, frecency: f64) -> f64 {
+    match_quality + (frecency.ln_1p() * 25.0).min(MAX_USAGE_BONUS)
+}
+
+/// Sorts by score, keeping the storage order for ties
+pub fn sort_by_score<T>(results: &mut [(f64, T)]) {
+    results.sort_by(|a, b| b.0.total_cmp(&a.0));
+}
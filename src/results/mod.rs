@@ -4,51 +4,132 @@ use sniffer_rs::sniffer::Sniffer;
 use whiskers_launcher_core::{
     features::extensions::{get_extension_setting, send_search_results, ExtensionRequest},
     results::{
-        CopyTextAction, FormField, FormFilePickerField, FormInputField, FormToggleField,
-        OpenFormAction, OpenLinkAction, ResultAction, RunExtensionAction, SearchResult,
-        SearchResults,
+        CopyTextAction, FormField, FormFilePickerField, FormInputField, FormSelectField,
+        FormSelectOption, FormToggleField, OpenFormAction, OpenLinkAction, ResultAction,
+        RunExtensionAction, SearchResult, SearchResults,
     },
     utils::get_search_query,
 };
 
-use crate::{icons::get_icon_path, settings::functions::get_settings, ID};
+use crate::{
+    error::{Error, Result},
+    icons::get_icon_path,
+    import::chromium::find_profiles,
+    ranking::{
+        get_bookmark_match_quality, get_group_match_quality, get_match_quality, get_score,
+        sort_by_score,
+    },
+    settings::{
+        functions::{get_active_settings_file, get_settings},
+        storage::SettingsFormat,
+        trash::TrashEntry,
+        ActionMode, Bookmark, Group, Settings,
+    },
+    usage::{get_now, get_usage, is_tracking_enabled, Usage},
+    ID,
+};
 
 pub fn on_get_results(request: ExtensionRequest) {
-    let search_text = request.search_text.unwrap();
-    let search = get_search_query(&search_text);
+    let search_text = request.search_text.unwrap_or_default();
+
+    if let Err(error) = get_results(&search_text) {
+        show_error_result(error);
+    }
+}
+
+fn get_results(search_text: &str) -> Result<()> {
+    let search = get_search_query(search_text);
 
     if search_text.trim().is_empty() {
-        show_default_results();
+        return show_default_results();
     }
 
-    if let Some(keyword) = search.keyword {
-        match keyword.as_str() {
-            "e" => show_edit_results(&search.search_text),
-            "edit" => show_edit_results(&search.search_text),
-            "d" => show_delete_results(&search.search_text),
-            "delete" => show_delete_results(&search.search_text),
-            _ => show_results(&search_text),
+    match search.keyword.as_deref() {
+        Some("e") | Some("edit") => show_edit_results(&search.search_text),
+        Some("d") | Some("delete") => show_delete_results(&search.search_text),
+        Some("i") | Some("import") => show_import_results(&search.search_text),
+        Some("broken") => show_broken_results(&search.search_text),
+        Some("duplicates") => show_duplicates_results(&search.search_text),
+        Some("trash") => show_trash_results(&search.search_text),
+        Some(keyword) => {
+            let settings = get_settings()?;
+
+            match settings.find_by_keyword(keyword) {
+                Some(bookmark) => show_keyword_result(bookmark, &search.search_text),
+                None => show_results(search_text),
+            }
         }
+        None => show_results(search_text),
     }
+}
+
+fn show_error_result(error: Error) {
+    let message = error.to_string();
 
-    show_results(&search_text);
+    let result = SearchResult::new(
+        &message,
+        ResultAction::new_copy_text_action(CopyTextAction::new(&message)),
+    )
+    .set_icon(get_icon_path("alert"))
+    .set_accent_icon_tint();
+
+    send_search_results(SearchResults::new_list_results(vec![result]));
 }
 
-fn show_default_results() {
+fn show_default_results() -> Result<()> {
     let mut results = Vec::<SearchResult>::new();
     let bookmark_fields = vec![
         FormField::new_input_field(
             "name",
-            FormInputField::new("Name", "The name of the bookmark")
-                .set_placeholder("Type the bookmark name")
-                .set_not_empty_validation(),
+            FormInputField::new(
+                "Name (Optional)",
+                "The name of the bookmark, read from the page title when empty",
+            )
+            .set_placeholder("Type the bookmark name"),
         ),
         FormField::new_input_field(
             "url",
-            FormInputField::new("Url", "The url of the bookmark")
-                .set_placeholder("Type the bookmark url")
-                .set_not_empty_validation(),
+            FormInputField::new(
+                "Url",
+                "The url of the bookmark, https:// is added when missing",
+            )
+            .set_placeholder("Type the bookmark url")
+            .set_not_empty_validation(),
+        ),
+        FormField::new_input_field(
+            "tags",
+            FormInputField::new(
+                "Tags (Optional)",
+                "Comma separated tags, search them with #tag",
+            )
+            .set_placeholder("Type the bookmark tags"),
+        ),
+        FormField::new_input_field(
+            "keyword",
+            FormInputField::new(
+                "Keyword (Optional)",
+                "Type it before a search to open the url with %s replaced by the search",
+            )
+            .set_placeholder("Type the bookmark keyword"),
         ),
+        FormField::new_input_field(
+            "aliases",
+            FormInputField::new(
+                "Aliases (Optional)",
+                "Comma separated names that show this bookmark first when typed exactly",
+            )
+            .set_placeholder("Type the bookmark aliases"),
+        ),
+        FormField::new_input_field(
+            "description",
+            FormInputField::new(
+                "Description (Optional)",
+                "Shown under the name and matched when searching",
+            )
+            .set_placeholder("Type the bookmark description"),
+        ),
+        get_action_mode_field(ActionMode::Default),
+        get_launch_command_field(None),
         FormField::new_toggle_field(
             "use-icon",
             FormToggleField::new(
@@ -57,6 +138,14 @@ fn show_default_results() {
                 true,
             ),
         ),
+        FormField::new_toggle_field(
+            "fetch-details",
+            FormToggleField::new(
+                "Fetch details",
+                "Fill the empty name and description from the page",
+                false,
+            ),
+        ),
     ];
 
     let mut group_fields = vec![
@@ -71,13 +160,44 @@ fn show_default_results() {
             FormFilePickerField::new("Icon (Optional)", "Select a icon for the group")
                 .set_image_file_types(),
         ),
+        FormField::new_input_field(
+            "aliases",
+            FormInputField::new(
+                "Aliases (Optional)",
+                "Comma separated names that show this group first when typed exactly",
+            )
+            .set_placeholder("Type the group aliases"),
+        ),
+        FormField::new_input_field(
+            "description",
+            FormInputField::new(
+                "Description (Optional)",
+                "Shown under the name and matched when searching",
+            )
+            .set_placeholder("Type the group description"),
+        ),
+        get_action_mode_field(ActionMode::Default),
+        get_launch_command_field(None),
         FormField::new_toggle_field(
             "tint-icon",
             FormToggleField::new("Tint icon", "Tint the group custom icon", false),
         ),
     ];
 
-    for bookmark in get_settings().bookmarks {
+    let settings = get_settings()?;
+
+    for group in &settings.groups {
+        group_fields.push(FormField::new_toggle_field(
+            format!("group-{}", group.id),
+            FormToggleField::new(
+                format!("Group || {}", &group.name),
+                "Toggle to open this group together with the new one",
+                false,
+            ),
+        ));
+    }
+
+    for bookmark in settings.bookmarks {
         group_fields.push(FormField::new_toggle_field(
             &bookmark.id.to_string(),
             FormToggleField::new(
@@ -114,16 +234,278 @@ fn show_default_results() {
         .set_accent_icon_tint(),
     );
 
+    results.push(get_import_result());
+    results.push(get_check_links_result());
+
+    let mut export_path = dirs::home_dir().unwrap_or_default();
+    export_path.push("bookmarks.html");
+
+    results.push(
+        SearchResult::new(
+            "Export Bookmarks",
+            ResultAction::new_open_form_action(
+                OpenFormAction::new(
+                    ID,
+                    "export-bookmarks",
+                    vec![FormField::new_input_field(
+                        "file",
+                        FormInputField::new("File", "Where to save the bookmarks.html file")
+                            .set_text(export_path.to_string_lossy())
+                            .set_placeholder("Type the file path")
+                            .set_not_empty_validation(),
+                    )],
+                )
+                .set_title("Export Bookmarks")
+                .set_action_text("Export"),
+            ),
+        )
+        .set_icon(get_icon_path("bookmark"))
+        .set_accent_icon_tint(),
+    );
+
+    let (title, format) = match get_active_settings_file().1 {
+        SettingsFormat::Binary => ("Convert Settings To Text (JSON)", "text"),
+        SettingsFormat::Text => ("Convert Settings To Binary", "binary"),
+    };
+
+    results.push(
+        SearchResult::new(
+            title,
+            ResultAction::new_run_extension_action(
+                RunExtensionAction::new(ID, "convert-settings").add_arg(format),
+            ),
+        )
+        .set_icon(get_icon_path("pencil"))
+        .set_accent_icon_tint(),
+    );
+
     send_search_results(SearchResults::new_list_results(results));
+
+    Ok(())
 }
 
-fn show_edit_results(search_text: &str) {
+fn get_import_result() -> SearchResult {
+    SearchResult::new(
+        "Import Bookmarks",
+        ResultAction::new_open_form_action(
+            OpenFormAction::new(
+                ID,
+                "import-bookmarks",
+                vec![FormField::new_file_picker_field(
+                    "file",
+                    FormFilePickerField::new(
+                        "File",
+                        "A bookmarks.html export, a Firefox places.sqlite or jsonlz4 backup, or a Chromium Bookmarks file",
+                    ),
+                )],
+            )
+            .set_title("Import Bookmarks")
+            .set_action_text("Import"),
+        ),
+    )
+    .set_icon(get_icon_path("plus"))
+    .set_accent_icon_tint()
+}
+
+fn get_check_links_result() -> SearchResult {
+    SearchResult::new(
+        "Check Links",
+        ResultAction::new_run_extension_action(RunExtensionAction::new(ID, "check-links")),
+    )
+    .set_description("Find bookmarks that are broken or moved, then type broken to see them")
+    .set_icon(get_icon_path("alert"))
+    .set_accent_icon_tint()
+}
+
+/// Broken bookmarks and offers to update the ones that moved for good
+fn show_broken_results(search_text: &str) -> Result<()> {
+    let settings = get_settings()?;
+    let sniffer = Sniffer::new();
+    let copy_url = get_extension_setting(ID, "copy-url").is_some_and(|v| v == "true");
+    let track_usage = is_tracking_enabled();
+    let mut results = vec![get_check_links_result()];
+    let mut matches = Vec::<(f64, SearchResult)>::new();
+
+    for bookmark in &settings.bookmarks {
+        let quality = match get_bookmark_match_quality(&sniffer, bookmark, search_text) {
+            Some(quality) => quality,
+            None => continue,
+        };
+
+        if let Some(redirect_url) = bookmark.get_permanent_redirect() {
+            let result = SearchResult::new(
+                format!("Update Url || {}", &bookmark.name),
+                ResultAction::new_run_extension_action(
+                    RunExtensionAction::new(ID, "update-redirect").add_arg(bookmark.id.to_string()),
+                ),
+            )
+            .set_description(format!("Moved to {}", redirect_url))
+            .set_icon(get_icon_path("pencil"))
+            .set_accent_icon_tint();
+
+            matches.push((quality, result));
+        }
+
+        if bookmark.is_broken() {
+            let result = get_bookmark_result(bookmark, copy_url, track_usage)
+                .set_icon(get_icon_path("alert"))
+                .set_accent_icon_tint();
+
+            matches.push((quality, result));
+        }
+    }
+
+    sort_by_score(&mut matches);
+
+    results.extend(matches.into_iter().map(|(_, result)| result));
+
+    send_search_results(SearchResults::new_list_results(results));
+
+    Ok(())
+}
+
+/// One merge result per group of bookmarks that point to the same page
+fn show_duplicates_results(search_text: &str) -> Result<()> {
+    let settings = get_settings()?;
+    let sniffer = Sniffer::new();
+    let mut results = Vec::<(f64, SearchResult)>::new();
+
+    for cluster in settings.get_duplicate_clusters() {
+        let quality = cluster
+            .iter()
+            .filter_map(|b| get_bookmark_match_quality(&sniffer, b, search_text))
+            .max_by(|a, b| a.total_cmp(b));
+
+        let quality = match quality {
+            Some(quality) => quality,
+            None => continue,
+        };
+
+        let mut action = RunExtensionAction::new(ID, "merge-duplicates");
+
+        for bookmark in &cluster {
+            action = action.add_arg(bookmark.id.to_string());
+        }
+
+        let urls: Vec<&str> = cluster.iter().map(|b| b.url.as_str()).collect();
+
+        let result = SearchResult::new(
+            format!("Merge {} Bookmarks || {}", cluster.len(), &cluster[0].name),
+            ResultAction::new_run_extension_action(action).set_dangerous(true),
+        )
+        .set_description(format!(
+            "Keeps {} and removes {}, their groups are kept",
+            urls[0],
+            urls[1..].join(", ")
+        ))
+        .set_icon(get_icon_path("bookmark"))
+        .set_accent_icon_tint();
+
+        results.push((quality, result));
+    }
+
+    sort_by_score(&mut results);
+
+    let results = results.into_iter().map(|(_, result)| result).collect();
+
+    send_search_results(SearchResults::new_list_results(results));
+
+    Ok(())
+}
+
+/// Restore and delete forever results for every trashed item, latest first
+fn show_trash_results(search_text: &str) -> Result<()> {
+    let settings = get_settings()?;
+    let sniffer = Sniffer::new();
+    let now = get_now();
     let mut results = Vec::<SearchResult>::new();
-    let settings = get_settings();
+
+    for item in settings.trash.iter().rev() {
+        let name = item.entry.get_name();
+
+        if get_match_quality(&sniffer, name, search_text).is_none() {
+            continue;
+        }
+
+        let (kind, id) = match &item.entry {
+            TrashEntry::Bookmark { bookmark, .. } => ("bookmark", bookmark.id),
+            TrashEntry::Group { group, .. } => ("group", group.id),
+        };
+
+        let days = now.saturating_sub(item.deleted_at) / (24 * 60 * 60);
+        let description = match days {
+            0 => "Deleted today".to_string(),
+            1 => "Deleted yesterday".to_string(),
+            days => format!("Deleted {} days ago", days),
+        };
+
+        results.push(
+            SearchResult::new(
+                format!("Restore || {}", name),
+                ResultAction::new_run_extension_action(
+                    RunExtensionAction::new(ID, "restore")
+                        .add_arg(kind)
+                        .add_arg(id.to_string()),
+                ),
+            )
+            .set_description(&description)
+            .set_icon(get_icon_path("plus"))
+            .set_accent_icon_tint(),
+        );
+
+        results.push(
+            SearchResult::new(
+                format!("Delete Forever || {}", name),
+                ResultAction::new_run_extension_action(
+                    RunExtensionAction::new(ID, "delete-forever")
+                        .add_arg(kind)
+                        .add_arg(id.to_string()),
+                )
+                .set_dangerous(true),
+            )
+            .set_description(&description)
+            .set_icon(get_icon_path("trash"))
+            .set_accent_icon_tint(),
+        );
+    }
+
+    send_search_results(SearchResults::new_list_results(results));
+
+    Ok(())
+}
+
+fn show_import_results(search_text: &str) -> Result<()> {
+    let mut results = vec![get_import_result()];
+    let sniffer = Sniffer::new();
+
+    for profile in find_profiles() {
+        if sniffer.matches(&profile.name, search_text) {
+            results.push(
+                SearchResult::new(
+                    format!("Import From {}", profile.name),
+                    ResultAction::new_run_extension_action(
+                        RunExtensionAction::new(ID, "import-browser")
+                            .add_arg(profile.path.to_string_lossy()),
+                    ),
+                )
+                .set_icon(get_icon_path("plus"))
+                .set_accent_icon_tint(),
+            );
+        }
+    }
+
+    send_search_results(SearchResults::new_list_results(results));
+
+    Ok(())
+}
+
+fn show_edit_results(search_text: &str) -> Result<()> {
+    let mut results = Vec::<(f64, SearchResult)>::new();
+    let settings = get_settings()?;
     let sniffer = Sniffer::new();
 
     for group in settings.to_owned().groups {
-        if sniffer.matches(&group.name, search_text) {
+        if let Some(quality) = get_group_match_quality(&sniffer, &group, search_text) {
             let bookmarks_ids = group.to_owned().bookmarks_ids;
 
             let name_field = FormField::new_input_field(
@@ -149,7 +531,51 @@ fn show_edit_results(search_text: &str) {
                 FormToggleField::new("Tint icon", "Tint the group custom icon", group.tint_icon),
             );
 
-            let mut fields = vec![name_field, icon_field, tint_icon_field];
+            let aliases_field = FormField::new_input_field(
+                "aliases",
+                FormInputField::new(
+                    "Aliases (Optional)",
+                    "Comma separated names that show this group first when typed exactly",
+                )
+                .set_text(group.aliases.join(", "))
+                .set_placeholder("Type the group aliases"),
+            );
+
+            let description_field = FormField::new_input_field(
+                "description",
+                FormInputField::new(
+                    "Description (Optional)",
+                    "Shown under the name and matched when searching",
+                )
+                .set_text(group.description.to_owned().unwrap_or_default())
+                .set_placeholder("Type the group description"),
+            );
+
+            let mut fields = vec![
+                name_field,
+                icon_field,
+                aliases_field,
+                description_field,
+                get_action_mode_field(group.action_mode),
+                get_launch_command_field(group.launch_command.as_deref()),
+                tint_icon_field,
+            ];
+
+            // Groups that contain this one can't be picked, they would create a cycle
+            for other_group in &settings.groups {
+                if settings.creates_cycle(group.id, &[other_group.id]) {
+                    continue;
+                }
+
+                fields.push(FormField::new_toggle_field(
+                    format!("group-{}", other_group.id),
+                    FormToggleField::new(
+                        format!("Group || {}", &other_group.name),
+                        "Toggle to open this group together with this one",
+                        group.groups_ids.contains(&other_group.id),
+                    ),
+                ));
+            }
 
             for bookmark in settings.to_owned().bookmarks {
                 let field = FormField::new_toggle_field(
@@ -187,12 +613,12 @@ fn show_edit_results(search_text: &str) {
                 edit_group_result = edit_group_result.set_accent_icon_tint();
             }
 
-            results.push(edit_group_result);
+            results.push((quality, edit_group_result));
         }
     }
 
     for bookmark in settings.bookmarks {
-        if sniffer.matches(&bookmark.name, search_text) {
+        if let Some(quality) = get_bookmark_match_quality(&sniffer, &bookmark, search_text) {
             let name_field = FormField::new_input_field(
                 "name",
                 FormInputField::new("Name", "The name of the bookmark")
@@ -203,10 +629,53 @@ fn show_edit_results(search_text: &str) {
 
             let url_field = FormField::new_input_field(
                 "url",
-                FormInputField::new("Url", "The url of the bookmark")
-                    .set_text(&bookmark.url)
-                    .set_placeholder("Type the bookmark url")
-                    .set_not_empty_validation(),
+                FormInputField::new(
+                    "Url",
+                    "The url of the bookmark, https:// is added when missing",
+                )
+                .set_text(&bookmark.url)
+                .set_placeholder("Type the bookmark url")
+                .set_not_empty_validation(),
+            );
+
+            let tags_field = FormField::new_input_field(
+                "tags",
+                FormInputField::new(
+                    "Tags (Optional)",
+                    "Comma separated tags, search them with #tag",
+                )
+                .set_text(bookmark.tags.join(", "))
+                .set_placeholder("Type the bookmark tags"),
+            );
+
+            let keyword_field = FormField::new_input_field(
+                "keyword",
+                FormInputField::new(
+                    "Keyword (Optional)",
+                    "Type it before a search to open the url with %s replaced by the search",
+                )
+                .set_text(bookmark.keyword.to_owned().unwrap_or_default())
+                .set_placeholder("Type the bookmark keyword"),
+            );
+
+            let aliases_field = FormField::new_input_field(
+                "aliases",
+                FormInputField::new(
+                    "Aliases (Optional)",
+                    "Comma separated names that show this bookmark first when typed exactly",
+                )
+                .set_text(bookmark.aliases.join(", "))
+                .set_placeholder("Type the bookmark aliases"),
+            );
+
+            let description_field = FormField::new_input_field(
+                "description",
+                FormInputField::new(
+                    "Description (Optional)",
+                    "Shown under the name and matched when searching",
+                )
+                .set_text(bookmark.description.to_owned().unwrap_or_default())
+                .set_placeholder("Type the bookmark description"),
             );
 
             let use_icon_field = FormField::new_toggle_field(
@@ -228,7 +697,17 @@ fn show_edit_results(search_text: &str) {
                     OpenFormAction::new(
                         ID,
                         "edit-bookmark",
-                        vec![name_field, url_field, use_icon_field],
+                        vec![
+                            name_field,
+                            url_field,
+                            tags_field,
+                            keyword_field,
+                            aliases_field,
+                            description_field,
+                            get_action_mode_field(bookmark.action_mode),
+                            get_launch_command_field(bookmark.launch_command.as_deref()),
+                            use_icon_field,
+                        ],
                     )
                     .set_title("Edit Bookmark")
                     .set_action_text("Save")
@@ -245,20 +724,26 @@ fn show_edit_results(search_text: &str) {
                 edit_bookmark_result = edit_bookmark_result.set_accent_icon_tint();
             }
 
-            results.push(edit_bookmark_result);
+            results.push((quality, edit_bookmark_result));
         }
     }
 
+    sort_by_score(&mut results);
+
+    let results = results.into_iter().map(|(_, result)| result).collect();
+
     send_search_results(SearchResults::new_list_results(results));
+
+    Ok(())
 }
 
-fn show_delete_results(search_text: &str) {
-    let mut results = Vec::<SearchResult>::new();
-    let settings = get_settings();
+fn show_delete_results(search_text: &str) -> Result<()> {
+    let mut results = Vec::<(f64, SearchResult)>::new();
+    let settings = get_settings()?;
     let sniffer = Sniffer::new();
 
     for group in settings.groups {
-        if sniffer.matches(&group.name, search_text) {
+        if let Some(quality) = get_group_match_quality(&sniffer, &group, search_text) {
             let mut result = SearchResult::new(
                 format!("Delete Group | {}", group.name),
                 ResultAction::new_run_extension_action(
@@ -279,12 +764,12 @@ fn show_delete_results(search_text: &str) {
                     .set_accent_icon_tint();
             }
 
-            results.push(result);
+            results.push((quality, result));
         }
     }
 
     for bookmark in settings.bookmarks {
-        if sniffer.matches(&bookmark.name, search_text) {
+        if let Some(quality) = get_bookmark_match_quality(&sniffer, &bookmark, search_text) {
             let mut result = SearchResult::new(
                 format!("Delete Bookmark | {}", bookmark.name),
                 ResultAction::new_run_extension_action(
@@ -301,70 +786,247 @@ fn show_delete_results(search_text: &str) {
                     .set_accent_icon_tint();
             }
 
-            results.push(result);
+            results.push((quality, result));
         }
     }
 
+    sort_by_score(&mut results);
+
+    let results = results.into_iter().map(|(_, result)| result).collect();
+
     send_search_results(SearchResults::new_list_results(results));
+
+    Ok(())
 }
 
-fn show_results(search_text: &str) {
-    let settings = get_settings();
-    let mut results = Vec::<SearchResult>::new();
-    let copy_url: bool = get_extension_setting(ID, "copy-url").unwrap() == "true";
-    let sniffer = Sniffer::new();
+fn show_keyword_result(bookmark: &Bookmark, query: &str) -> Result<()> {
+    let copy_url = get_extension_setting(ID, "copy-url").is_some_and(|v| v == "true");
+    let action = get_bookmark_action(bookmark, query, copy_url, is_tracking_enabled());
 
-    if !copy_url {
-        for group in settings.groups {
-            if sniffer.matches(&group.name, search_text) {
-                let mut result = SearchResult::new(
-                    &group.name,
-                    ResultAction::new_run_extension_action(
-                        RunExtensionAction::new(ID, "open-group").add_arg(&group.id.to_string()),
-                    ),
-                );
+    let mut result = SearchResult::new(format!("{} || {}", &bookmark.name, query), action);
 
-                if let Some(icon_path) = group.icon_path {
-                    result = result.set_icon(PathBuf::from(icon_path));
+    if let Some(icon_path) = &bookmark.icon_path {
+        result = result.set_icon(PathBuf::from(icon_path));
+    } else {
+        result = result
+            .set_icon(get_icon_path("bookmark"))
+            .set_accent_icon_tint();
+    }
 
-                    if group.tint_icon {
-                        result = result.set_accent_icon_tint();
-                    }
-                } else {
-                    result = result
-                        .set_icon(get_icon_path("folder"))
-                        .set_accent_icon_tint();
-                }
+    send_search_results(SearchResults::new_list_results(vec![result]));
 
-                results.push(result);
-            }
+    Ok(())
+}
+
+/// Splits `#tag` filters from the rest of the search
+fn get_tag_filters(search_text: &str) -> (Vec<String>, String) {
+    let mut tags = Vec::<String>::new();
+    let mut words = Vec::<&str>::new();
+
+    for word in search_text.split_whitespace() {
+        match word.strip_prefix('#') {
+            Some(tag) if !tag.is_empty() => tags.push(tag.to_string()),
+            _ => words.push(word),
         }
     }
 
-    for bookmark in settings.bookmarks {
-        if sniffer.matches(&bookmark.name, search_text) {
-            let mut result = match copy_url {
-                true => SearchResult::new(
-                    &bookmark.name,
-                    ResultAction::new_copy_text_action(CopyTextAction::new(&bookmark.url)),
-                ),
-                false => SearchResult::new(
-                    &bookmark.name,
-                    ResultAction::new_open_link_action(OpenLinkAction::new(&bookmark.url)),
-                ),
-            };
+    (tags, words.join(" "))
+}
 
-            if let Some(icon_path) = bookmark.icon_path {
-                result = result.set_icon(PathBuf::from(icon_path));
-            } else {
-                result = result
-                    .set_icon(get_icon_path("bookmark"))
-                    .set_accent_icon_tint();
+fn show_results(search_text: &str) -> Result<()> {
+    let settings = get_settings()?;
+    let mut results = Vec::<(f64, SearchResult)>::new();
+    let mut alias_results = Vec::<SearchResult>::new();
+    let copy_url = get_extension_setting(ID, "copy-url").is_some_and(|v| v == "true");
+    let track_usage = is_tracking_enabled();
+    let usage = if track_usage {
+        get_usage()
+    } else {
+        Usage::default()
+    };
+    let now = get_now();
+    let sniffer = Sniffer::new();
+    let (tags, search_text) = get_tag_filters(search_text);
+    let search_text = search_text.as_str();
+
+    // Groups have no tags, so they are hidden while filtering by them
+    if tags.is_empty() {
+        for group in &settings.groups {
+            if group.has_alias(search_text) {
+                alias_results.push(get_group_result(&settings, group, copy_url));
+                continue;
             }
 
-            results.push(result);
+            if let Some(quality) = get_group_match_quality(&sniffer, group, search_text) {
+                let frecency = usage.get_group_frecency(group.id, now);
+                let result = get_group_result(&settings, group, copy_url);
+
+                results.push((get_score(quality, frecency), result));
+            }
         }
     }
 
-    send_search_results(SearchResults::new_list_results(results));
+    for bookmark in &settings.bookmarks {
+        if !tags.iter().all(|tag| bookmark.has_tag(tag)) {
+            continue;
+        }
+
+        if bookmark.has_alias(search_text) {
+            alias_results.push(get_bookmark_result(bookmark, copy_url, track_usage));
+            continue;
+        }
+
+        if let Some(quality) = get_bookmark_match_quality(&sniffer, bookmark, search_text) {
+            let frecency = usage.get_bookmark_frecency(bookmark.id, now);
+            let result = get_bookmark_result(bookmark, copy_url, track_usage);
+
+            results.push((get_score(quality, frecency), result));
+        }
+    }
+
+    // An alias names a single bookmark or group, so the other matches are just noise
+    if alias_results.len() == 1 {
+        send_search_results(SearchResults::new_list_results(alias_results));
+        return Ok(());
+    }
+
+    sort_by_score(&mut results);
+
+    alias_results.extend(results.into_iter().map(|(_, result)| result));
+
+    send_search_results(SearchResults::new_list_results(alias_results));
+
+    Ok(())
+}
+
+fn get_group_result(settings: &Settings, group: &Group, copy_url: bool) -> SearchResult {
+    let mut result = SearchResult::new(&group.name, get_group_action(settings, group, copy_url));
+
+    if let Some(description) = &group.description {
+        result = result.set_description(description);
+    }
+
+    if let Some(icon_path) = &group.icon_path {
+        result = result.set_icon(PathBuf::from(icon_path));
+
+        if group.tint_icon {
+            result = result.set_accent_icon_tint();
+        }
+    } else {
+        result = result
+            .set_icon(get_icon_path("folder"))
+            .set_accent_icon_tint();
+    }
+
+    result
+}
+
+/// Copy actions copy every url of the group, one per line
+fn get_group_action(settings: &Settings, group: &Group, copy_url: bool) -> ResultAction {
+    let bookmarks = settings.get_group_bookmarks(group.id);
+
+    let lines: Vec<String> = match group.action_mode.resolve(copy_url) {
+        ActionMode::CopyUrl => bookmarks.iter().map(|b| b.url.to_owned()).collect(),
+        ActionMode::CopyMarkdown => bookmarks
+            .iter()
+            .map(|b| format!("- {}", b.get_markdown_link(&b.url)))
+            .collect(),
+        _ => {
+            return ResultAction::new_run_extension_action(
+                RunExtensionAction::new(ID, "open-group").add_arg(group.id.to_string()),
+            )
+        }
+    };
+
+    ResultAction::new_copy_text_action(CopyTextAction::new(lines.join("\n")))
+}
+
+fn get_bookmark_result(bookmark: &Bookmark, copy_url: bool, track_usage: bool) -> SearchResult {
+    // Keyword bookmarks opened without a query get an empty one
+    let action = get_bookmark_action(bookmark, "", copy_url, track_usage);
+    let mut result = SearchResult::new(&bookmark.name, action);
+
+    if let Some(description) = get_bookmark_description(bookmark) {
+        result = result.set_description(description);
+    }
+
+    if let Some(icon_path) = &bookmark.icon_path {
+        result = result.set_icon(PathBuf::from(icon_path));
+    } else {
+        result = result
+            .set_icon(get_icon_path("bookmark"))
+            .set_accent_icon_tint();
+    }
+
+    result
+}
+
+/// Flags broken links before the description
+fn get_bookmark_description(bookmark: &Bookmark) -> Option<String> {
+    let broken = bookmark
+        .link_check
+        .as_ref()
+        .filter(|check| check.is_broken())
+        .map(|check| format!("Broken link ({})", check.get_reason()));
+
+    match (broken, &bookmark.description) {
+        (Some(broken), Some(description)) => Some(format!("{} · {}", broken, description)),
+        (Some(broken), None) => Some(broken),
+        (None, description) => description.to_owned(),
+    }
+}
+
+fn get_bookmark_action(
+    bookmark: &Bookmark,
+    query: &str,
+    copy_url: bool,
+    track_usage: bool,
+) -> ResultAction {
+    let url = bookmark.get_url_for(query);
+
+    // Opening through the extension lets it count the visit and use the launch command
+    match bookmark.action_mode.resolve(copy_url) {
+        ActionMode::CopyUrl => ResultAction::new_copy_text_action(CopyTextAction::new(&url)),
+        ActionMode::CopyMarkdown => ResultAction::new_copy_text_action(CopyTextAction::new(
+            bookmark.get_markdown_link(&url),
+        )),
+        _ if track_usage || bookmark.launch_command.is_some() => {
+            ResultAction::new_run_extension_action(
+                RunExtensionAction::new(ID, "open-bookmark")
+                    .add_arg(bookmark.id.to_string())
+                    .add_arg(query),
+            )
+        }
+        _ => ResultAction::new_open_link_action(OpenLinkAction::new(&url)),
+    }
+}
+
+/// Select field to override the `copy-url` setting for a single bookmark or group
+fn get_action_mode_field(action_mode: ActionMode) -> FormField {
+    let options = ActionMode::ALL
+        .iter()
+        .map(|mode| FormSelectOption::new(mode.get_id(), mode.get_text()))
+        .collect();
+
+    FormField::new_select_field(
+        "action-mode",
+        FormSelectField::new(
+            "Action",
+            "What selecting it does",
+            action_mode.get_id(),
+            options,
+        ),
+    )
+}
+
+fn get_launch_command_field(launch_command: Option<&str>) -> FormField {
+    FormField::new_input_field(
+        "launch-command",
+        FormInputField::new(
+            "Launch Command (Optional)",
+            "Opens the urls with this command instead of the default browser. {url} is replaced by the url",
+        )
+        .set_text(launch_command.unwrap_or_default())
+        .set_placeholder("firefox --private-window {url}"),
+    )
 }
@@ -1,41 +1,140 @@
-use std::fs;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
 
-use crate::paths::get_settings_path;
+use fs4::fs_std::FileExt;
 
-use super::Settings;
+use crate::{
+    error::Result,
+    paths::{get_settings_lock_path, get_settings_path, get_text_settings_path},
+};
 
-pub fn get_settings() -> Settings {
-    let path = get_settings_path();
+use super::{storage::SettingsFormat, Settings};
 
-    if !path.parent().unwrap().exists() {
-        fs::create_dir_all(&path.parent().unwrap()).expect("Error creating settings directory");
+/// Returns the settings file in use and its format. `settings.json` wins when both exist
+pub fn get_active_settings_file() -> (PathBuf, SettingsFormat) {
+    let text_path = get_text_settings_path();
+
+    if text_path.exists() {
+        return (text_path, SettingsFormat::Text);
+    }
+
+    (get_settings_path(), SettingsFormat::Binary)
+}
+
+pub fn get_settings() -> Result<Settings> {
+    let (path, format) = get_active_settings_file();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
 
     if !path.exists() {
         let default_settings = Settings {
             groups: Vec::new(),
             bookmarks: Vec::new(),
+            trash: Vec::new(),
+            bookmark_id_counter: 0,
+            group_id_counter: 0,
         };
 
-        fs::write(
-            &path,
-            &bincode::serialize(&default_settings).expect("Error serializing settings"),
-        )
-        .expect("Error writing default settings");
+        write_atomically(&path, &format.encode(&default_settings))?;
 
-        return default_settings.to_owned();
+        return Ok(default_settings);
     }
 
-    let bytes = fs::read(&path).expect("Error reading settings file");
-    let settings = bincode::deserialize(&bytes).expect("Error deserializing settings file");
-    settings
+    let bytes = fs::read(&path)?;
+
+    Ok(format.decode(&bytes)?)
 }
 
-pub fn write_settings(settings: Settings) {
+pub fn write_settings(settings: Settings) -> Result<()> {
     let mut settings = settings;
     settings.groups.sort_by_key(|g| g.id.to_owned());
     settings.bookmarks.sort_by_key(|b| b.id.to_owned());
+    settings.update_id_counters();
+
+    let (path, format) = get_active_settings_file();
+
+    write_atomically(&path, &format.encode(&settings))?;
+
+    Ok(())
+}
+
+/// Rewrites the settings in `format` and removes the file of the other format
+pub fn convert_settings(format: SettingsFormat) -> Result<()> {
+    let settings = get_settings()?;
+    let (old_path, _) = get_active_settings_file();
+
+    let new_path = match format {
+        SettingsFormat::Binary => get_settings_path(),
+        SettingsFormat::Text => get_text_settings_path(),
+    };
+
+    write_atomically(&new_path, &format.encode(&settings))?;
+
+    if old_path != new_path && old_path.exists() {
+        fs::remove_file(&old_path)?;
+    }
+
+    Ok(())
+}
+
+/// Writes to a temporary file next to `path` and renames it over, so a crash never leaves a
+/// truncated settings file behind
+pub fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    // The pid keeps two launcher invocations from writing the same temporary file
+    let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+
+    fs::rename(&temp_path, path)?;
+
+    // Persist the rename itself. Directories can't be opened like this on windows
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+/// Exclusive advisory lock on a file, released when dropped
+pub struct FileLock {
+    file: File,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// Blocks until no other launcher invocation is modifying the settings. Hold it for the whole
+/// load, modify and save cycle so concurrent commands don't overwrite each other
+pub fn lock_settings() -> Result<FileLock> {
+    lock_file(&get_settings_lock_path())
+}
+
+/// Blocks until nobody else holds the lock on `path`, creating it when missing
+pub fn lock_file(path: &Path) -> Result<FileLock> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+
+    file.lock_exclusive()?;
 
-    let bytes = bincode::serialize(&settings).expect("Error serializing settings file");
-    fs::write(get_settings_path(), &bytes).expect("Error writing settings file");
+    Ok(FileLock { file })
 }
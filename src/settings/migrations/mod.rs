@@ -0,0 +1,158 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+use super::Settings;
+
+mod v0;
+mod v10;
+mod v11;
+mod v12;
+mod v2;
+mod v3;
+mod v4;
+mod v5;
+mod v6;
+mod v7;
+mod v8;
+mod v9;
+
+#[cfg(test)]
+mod tests;
+
+/// Marks a settings file that starts with a version header. Files without it are version 0
+pub const MAGIC: &[u8; 4] = b"WLBK";
+
+/// The schema version written by this build. Bump it and add a migration whenever
+/// `Settings`, `Group` or `Bookmark` change
+pub const CURRENT_VERSION: u32 = 13;
+
+#[derive(Debug, Clone)]
+pub enum SettingsError {
+    NewerVersion(u32),
+    Corrupted(String),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::NewerVersion(version) => write!(
+                f,
+                "The settings file uses version {} but this extension only supports up to version {}. Please update the extension",
+                version, CURRENT_VERSION
+            ),
+            SettingsError::Corrupted(error) => {
+                write!(f, "The settings file could not be read: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+pub fn encode(settings: &Settings) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&CURRENT_VERSION.to_le_bytes());
+    bytes.extend(bincode::serialize(settings).expect("Error serializing settings"));
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Settings, SettingsError> {
+    let (version, body) = split_header(bytes)?;
+
+    migrate(version, body)
+}
+
+/// Upgrades the JSON of `settings.json`, whose version is stored next to the data
+pub fn decode_text(version: u32, value: serde_json::Value) -> Result<Settings, SettingsError> {
+    migrate(version, value)
+}
+
+/// The body of a settings file, read with the layout of its version
+trait Body {
+    fn parse<T: DeserializeOwned>(self) -> Result<T, SettingsError>;
+}
+
+impl Body for &[u8] {
+    fn parse<T: DeserializeOwned>(self) -> Result<T, SettingsError> {
+        bincode::deserialize(self).map_err(|e| SettingsError::Corrupted(e.to_string()))
+    }
+}
+
+impl Body for serde_json::Value {
+    fn parse<T: DeserializeOwned>(self) -> Result<T, SettingsError> {
+        serde_json::from_value(self).map_err(|e| SettingsError::Corrupted(e.to_string()))
+    }
+}
+
+fn split_header(bytes: &[u8]) -> Result<(u32, &[u8]), SettingsError> {
+    if !bytes.starts_with(MAGIC) {
+        return Ok((0, bytes));
+    }
+
+    let version_bytes: [u8; 4] = bytes
+        .get(MAGIC.len()..MAGIC.len() + 4)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| SettingsError::Corrupted("Truncated version header".to_string()))?;
+
+    Ok((u32::from_le_bytes(version_bytes), &bytes[MAGIC.len() + 4..]))
+}
+
+/// Decodes the body with the layout of `version` and upgrades it one step at a time
+fn migrate(version: u32, body: impl Body) -> Result<Settings, SettingsError> {
+    if version > CURRENT_VERSION {
+        return Err(SettingsError::NewerVersion(version));
+    }
+
+    match version {
+        0 | 1 => {
+            let settings: v0::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        2 => {
+            let settings: v2::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        3 => {
+            let settings: v3::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        4 => {
+            let settings: v4::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        5 => {
+            let settings: v5::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        6 => {
+            let settings: v6::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        7 => {
+            let settings: v7::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        8 => {
+            let settings: v8::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        9 => {
+            let settings: v9::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        10 => {
+            let settings: v10::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        11 => {
+            let settings: v11::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        12 => {
+            let settings: v12::Settings = body.parse()?;
+            Ok(settings.upgrade())
+        }
+        _ => body.parse(),
+    }
+}
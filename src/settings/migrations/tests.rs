@@ -0,0 +1,503 @@
+use serde::Serialize;
+
+use crate::settings::{storage::SettingsFormat, ActionMode};
+
+use super::*;
+
+fn encode_version(version: u32, settings: &impl Serialize) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend(bincode::serialize(settings).unwrap());
+    bytes
+}
+
+fn get_v0_settings() -> v0::Settings {
+    v0::Settings {
+        groups: vec![v0::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+        }],
+        bookmarks: vec![v0::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+        }],
+    }
+}
+
+/// Fields every version has
+fn assert_common(settings: &Settings) {
+    let group = &settings.groups[0];
+    assert_eq!(group.id, 0);
+    assert_eq!(group.icon_path.as_deref(), Some("group.png"));
+    assert!(group.tint_icon);
+    assert_eq!(group.name, "Work");
+    assert_eq!(group.bookmarks_ids, vec![1]);
+
+    let bookmark = &settings.bookmarks[0];
+    assert_eq!(bookmark.id, 1);
+    assert_eq!(bookmark.name, "GitHub");
+    assert_eq!(bookmark.url, "https://github.com/");
+}
+
+#[test]
+fn decodes_version_0_without_header() {
+    let bytes = bincode::serialize(&get_v0_settings()).unwrap();
+    let settings = decode(&bytes).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.bookmarks[0].created_at, None);
+}
+
+#[test]
+fn decodes_version_1() {
+    let settings = decode(&encode_version(1, &get_v0_settings())).unwrap();
+
+    assert_common(&settings);
+}
+
+#[test]
+fn decodes_version_2() {
+    let old = v2::Settings {
+        groups: vec![v2::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+        }],
+        bookmarks: vec![v2::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+        }],
+    };
+
+    let settings = decode(&encode_version(2, &old)).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.bookmarks[0].created_at, Some(5));
+}
+
+#[test]
+fn decodes_version_3() {
+    let old = v3::Settings {
+        groups: vec![v3::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+        }],
+        bookmarks: vec![v3::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec!["code".to_string()],
+        }],
+    };
+
+    let settings = decode(&encode_version(3, &old)).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.bookmarks[0].tags, vec!["code"]);
+}
+
+#[test]
+fn decodes_version_4() {
+    let group = |id: usize, bookmarks_ids: Vec<usize>, groups_ids: Vec<usize>| v4::Group {
+        id,
+        icon_path: Some("group.png".to_string()),
+        tint_icon: true,
+        name: "Work".to_string(),
+        bookmarks_ids,
+        groups_ids,
+    };
+    let old = v4::Settings {
+        groups: vec![group(0, vec![1], vec![1]), group(1, vec![], vec![])],
+        bookmarks: vec![v4::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec!["code".to_string()],
+        }],
+    };
+
+    let settings = decode(&encode_version(4, &old)).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.groups[0].groups_ids, vec![1]);
+    assert_eq!(settings.bookmarks[0].tags, vec!["code"]);
+}
+
+#[test]
+fn decodes_version_5() {
+    let old = v5::Settings {
+        groups: vec![v5::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+            groups_ids: vec![],
+        }],
+        bookmarks: vec![v5::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec!["code".to_string()],
+            keyword: Some("gh".to_string()),
+        }],
+    };
+
+    let settings = decode(&encode_version(5, &old)).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.bookmarks[0].keyword.as_deref(), Some("gh"));
+}
+
+#[test]
+fn decodes_version_6() {
+    let old = v6::Settings {
+        groups: vec![v6::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+            groups_ids: vec![],
+            aliases: vec!["w".to_string()],
+        }],
+        bookmarks: vec![v6::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec!["code".to_string()],
+            keyword: Some("gh".to_string()),
+            aliases: vec!["hub".to_string()],
+        }],
+    };
+
+    let settings = decode(&encode_version(6, &old)).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.groups[0].aliases, vec!["w"]);
+    assert_eq!(settings.bookmarks[0].aliases, vec!["hub"]);
+    assert_eq!(settings.bookmarks[0].keyword.as_deref(), Some("gh"));
+}
+
+fn get_v7_settings() -> v7::Settings {
+    v7::Settings {
+        groups: vec![v7::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+            groups_ids: vec![],
+            aliases: vec!["w".to_string()],
+        }],
+        bookmarks: vec![v7::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec!["code".to_string()],
+            keyword: Some("gh".to_string()),
+            aliases: vec!["hub".to_string()],
+            notes: Some("Code hosting".to_string()),
+        }],
+    }
+}
+
+#[test]
+fn decodes_version_7() {
+    let settings = decode(&encode_version(7, &get_v7_settings())).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(
+        settings.bookmarks[0].description.as_deref(),
+        Some("Code hosting")
+    );
+}
+
+#[test]
+fn decodes_version_7_text() {
+    let mut value = serde_json::to_value(get_v7_settings()).unwrap();
+    value["version"] = 7.into();
+    let bytes = serde_json::to_vec(&value).unwrap();
+
+    let settings = SettingsFormat::Text.decode(&bytes).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(
+        settings.bookmarks[0].description.as_deref(),
+        Some("Code hosting")
+    );
+}
+
+#[test]
+fn decodes_version_8() {
+    let old = v8::Settings {
+        groups: vec![v8::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+            groups_ids: vec![],
+            aliases: vec![],
+            description: Some("Daily".to_string()),
+        }],
+        bookmarks: vec![v8::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec![],
+            keyword: None,
+            aliases: vec![],
+            description: Some("Code hosting".to_string()),
+        }],
+    };
+
+    let settings = decode(&encode_version(8, &old)).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.groups[0].description.as_deref(), Some("Daily"));
+    assert_eq!(
+        settings.bookmarks[0].description.as_deref(),
+        Some("Code hosting")
+    );
+}
+
+#[test]
+fn decodes_version_9() {
+    let old = v9::Settings {
+        groups: vec![v9::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+            groups_ids: vec![],
+            aliases: vec![],
+            description: None,
+            action_mode: v9::ActionMode::CopyUrl,
+        }],
+        bookmarks: vec![v9::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec![],
+            keyword: None,
+            aliases: vec![],
+            description: None,
+            action_mode: v9::ActionMode::CopyMarkdown,
+        }],
+    };
+
+    let settings = decode(&encode_version(9, &old)).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.groups[0].action_mode, ActionMode::CopyUrl);
+    assert_eq!(settings.bookmarks[0].action_mode, ActionMode::CopyMarkdown);
+}
+
+#[test]
+fn decodes_version_10() {
+    let old = v10::Settings {
+        groups: vec![v10::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+            groups_ids: vec![],
+            aliases: vec![],
+            description: None,
+            action_mode: v10::ActionMode::Open,
+            launch_command: Some("firefox --new-window".to_string()),
+        }],
+        bookmarks: vec![v10::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec![],
+            keyword: None,
+            aliases: vec![],
+            description: None,
+            action_mode: v10::ActionMode::Default,
+            launch_command: Some("chromium".to_string()),
+        }],
+    };
+
+    let settings = decode(&encode_version(10, &old)).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(
+        settings.groups[0].launch_command.as_deref(),
+        Some("firefox --new-window")
+    );
+    assert_eq!(
+        settings.bookmarks[0].launch_command.as_deref(),
+        Some("chromium")
+    );
+}
+
+#[test]
+fn decodes_version_11() {
+    let old = v11::Settings {
+        groups: vec![v11::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+            groups_ids: vec![],
+            aliases: vec![],
+            description: None,
+            action_mode: v11::ActionMode::Default,
+            launch_command: None,
+        }],
+        bookmarks: vec![v11::Bookmark {
+            id: 1,
+            icon_path: None,
+            name: "GitHub".to_string(),
+            url: "https://github.com/".to_string(),
+            created_at: Some(5),
+            tags: vec![],
+            keyword: None,
+            aliases: vec![],
+            description: None,
+            action_mode: v11::ActionMode::Default,
+            launch_command: None,
+            link_check: Some(v11::LinkCheck {
+                status: Some(200),
+                error: None,
+                redirect_url: Some("https://github.com/home".to_string()),
+                permanent_redirect: true,
+                checked_at: 10,
+            }),
+        }],
+    };
+
+    let settings = decode(&encode_version(11, &old)).unwrap();
+
+    assert_common(&settings);
+
+    let link_check = settings.bookmarks[0].link_check.as_ref().unwrap();
+    assert_eq!(link_check.status, Some(200));
+    assert_eq!(
+        link_check.redirect_url.as_deref(),
+        Some("https://github.com/home")
+    );
+    assert!(link_check.permanent_redirect);
+    assert_eq!(link_check.checked_at, 10);
+}
+
+fn get_v12_bookmark(id: usize, name: &str) -> v12::Bookmark {
+    v12::Bookmark {
+        id,
+        icon_path: None,
+        name: name.to_string(),
+        url: "https://github.com/".to_string(),
+        created_at: Some(5),
+        tags: vec![],
+        keyword: None,
+        aliases: vec![],
+        description: None,
+        action_mode: v12::ActionMode::Default,
+        launch_command: None,
+        link_check: None,
+    }
+}
+
+fn get_v12_settings() -> v12::Settings {
+    v12::Settings {
+        groups: vec![v12::Group {
+            id: 0,
+            icon_path: Some("group.png".to_string()),
+            tint_icon: true,
+            name: "Work".to_string(),
+            bookmarks_ids: vec![1],
+            groups_ids: vec![],
+            aliases: vec![],
+            description: None,
+            action_mode: v12::ActionMode::Default,
+            launch_command: None,
+        }],
+        bookmarks: vec![get_v12_bookmark(1, "GitHub")],
+        trash: vec![v12::TrashItem {
+            deleted_at: 20,
+            entry: v12::TrashEntry::Bookmark {
+                bookmark: get_v12_bookmark(4, "Old"),
+                groups_ids: vec![0],
+            },
+        }],
+    }
+}
+
+#[test]
+fn decodes_version_12() {
+    let settings = decode(&encode_version(12, &get_v12_settings())).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.trash[0].deleted_at, 20);
+    assert!(settings.trash[0].entry.is_bookmark(4));
+    assert_eq!(settings.next_bookmark_id(), 5);
+}
+
+#[test]
+fn round_trips_current_version() {
+    let settings = get_v0_settings().upgrade();
+    let settings = decode(&encode(&settings)).unwrap();
+
+    assert_common(&settings);
+}
+
+#[test]
+fn rejects_newer_versions() {
+    let bytes = encode_version(CURRENT_VERSION + 1, &get_v0_settings());
+
+    assert!(matches!(
+        decode(&bytes),
+        Err(SettingsError::NewerVersion(version)) if version == CURRENT_VERSION + 1
+    ));
+}
+
+#[test]
+fn drops_dangling_references_from_text_files() {
+    let mut old = get_v12_settings();
+    old.groups[0].bookmarks_ids.push(9);
+
+    let mut value = serde_json::to_value(old).unwrap();
+    value["version"] = 12.into();
+    let bytes = serde_json::to_vec(&value).unwrap();
+
+    let settings = SettingsFormat::Text.decode(&bytes).unwrap();
+
+    assert_common(&settings);
+    assert_eq!(settings.bookmark_id_counter, 10);
+    assert_eq!(settings.next_bookmark_id(), 10);
+}
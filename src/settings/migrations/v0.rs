@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings;
+
+use super::v2;
+
+/// Layout of versions 0 and 1. Version 0 files have no header, version 1 only added it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub groups: Vec<Group>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Group {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub tint_icon: bool,
+    pub name: String,
+    pub bookmarks_ids: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub name: String,
+    pub url: String,
+}
+
+impl Settings {
+    pub fn upgrade(self) -> settings::Settings {
+        v2::Settings::from(self).upgrade()
+    }
+}
+
+impl From<Settings> for v2::Settings {
+    fn from(settings: Settings) -> Self {
+        Self {
+            groups: settings
+                .groups
+                .into_iter()
+                .map(|g| v2::Group {
+                    id: g.id,
+                    icon_path: g.icon_path,
+                    tint_icon: g.tint_icon,
+                    name: g.name,
+                    bookmarks_ids: g.bookmarks_ids,
+                })
+                .collect(),
+            bookmarks: settings
+                .bookmarks
+                .into_iter()
+                .map(|b| v2::Bookmark {
+                    id: b.id,
+                    icon_path: b.icon_path,
+                    name: b.name,
+                    url: b.url,
+                    created_at: None,
+                })
+                .collect(),
+        }
+    }
+}
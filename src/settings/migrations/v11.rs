@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings;
+
+use super::v12;
+
+/// Layout of version 11, which added `Bookmark::link_check`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub groups: Vec<Group>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Group {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub tint_icon: bool,
+    pub name: String,
+    pub bookmarks_ids: Vec<usize>,
+    pub groups_ids: Vec<usize>,
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub action_mode: ActionMode,
+    pub launch_command: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub name: String,
+    pub url: String,
+    pub created_at: Option<u64>,
+    pub tags: Vec<String>,
+    pub keyword: Option<String>,
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub action_mode: ActionMode,
+    pub launch_command: Option<String>,
+    pub link_check: Option<LinkCheck>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LinkCheck {
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub redirect_url: Option<String>,
+    pub permanent_redirect: bool,
+    pub checked_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ActionMode {
+    Default,
+    Open,
+    CopyUrl,
+    CopyMarkdown,
+}
+
+impl Settings {
+    pub fn upgrade(self) -> settings::Settings {
+        v12::Settings::from(self).upgrade()
+    }
+}
+
+impl From<Settings> for v12::Settings {
+    fn from(settings: Settings) -> Self {
+        Self {
+            groups: settings
+                .groups
+                .into_iter()
+                .map(|g| v12::Group {
+                    id: g.id,
+                    icon_path: g.icon_path,
+                    tint_icon: g.tint_icon,
+                    name: g.name,
+                    bookmarks_ids: g.bookmarks_ids,
+                    groups_ids: g.groups_ids,
+                    aliases: g.aliases,
+                    description: g.description,
+                    action_mode: g.action_mode.into(),
+                    launch_command: g.launch_command,
+                })
+                .collect(),
+            bookmarks: settings
+                .bookmarks
+                .into_iter()
+                .map(|b| v12::Bookmark {
+                    id: b.id,
+                    icon_path: b.icon_path,
+                    name: b.name,
+                    url: b.url,
+                    created_at: b.created_at,
+                    tags: b.tags,
+                    keyword: b.keyword,
+                    aliases: b.aliases,
+                    description: b.description,
+                    action_mode: b.action_mode.into(),
+                    launch_command: b.launch_command,
+                    link_check: b.link_check.map(|c| c.into()),
+                })
+                .collect(),
+            trash: Vec::new(),
+        }
+    }
+}
+
+impl From<ActionMode> for v12::ActionMode {
+    fn from(mode: ActionMode) -> Self {
+        match mode {
+            ActionMode::Default => Self::Default,
+            ActionMode::Open => Self::Open,
+            ActionMode::CopyUrl => Self::CopyUrl,
+            ActionMode::CopyMarkdown => Self::CopyMarkdown,
+        }
+    }
+}
+
+impl From<LinkCheck> for v12::LinkCheck {
+    fn from(check: LinkCheck) -> Self {
+        Self {
+            status: check.status,
+            error: check.error,
+            redirect_url: check.redirect_url,
+            permanent_redirect: check.permanent_redirect,
+            checked_at: check.checked_at,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings;
+
+/// Layout of version 12, which added `Settings::trash`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub groups: Vec<Group>,
+    pub bookmarks: Vec<Bookmark>,
+    pub trash: Vec<TrashItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Group {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub tint_icon: bool,
+    pub name: String,
+    pub bookmarks_ids: Vec<usize>,
+    pub groups_ids: Vec<usize>,
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub action_mode: ActionMode,
+    pub launch_command: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub name: String,
+    pub url: String,
+    pub created_at: Option<u64>,
+    pub tags: Vec<String>,
+    pub keyword: Option<String>,
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub action_mode: ActionMode,
+    pub launch_command: Option<String>,
+    pub link_check: Option<LinkCheck>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashItem {
+    pub deleted_at: u64,
+    pub entry: TrashEntry,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum TrashEntry {
+    Bookmark {
+        bookmark: Bookmark,
+        groups_ids: Vec<usize>,
+    },
+    Group {
+        group: Group,
+        parents_ids: Vec<usize>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LinkCheck {
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub redirect_url: Option<String>,
+    pub permanent_redirect: bool,
+    pub checked_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ActionMode {
+    Default,
+    Open,
+    CopyUrl,
+    CopyMarkdown,
+}
+
+impl Settings {
+    /// Deleting used to leave the ids of deleted bookmarks in their groups
+    pub fn upgrade(self) -> settings::Settings {
+        let mut settings: settings::Settings = self.into();
+        settings.remove_dangling_references();
+        settings
+    }
+}
+
+impl From<Settings> for settings::Settings {
+    fn from(settings: Settings) -> Self {
+        Self {
+            groups: settings.groups.into_iter().map(|g| g.into()).collect(),
+            bookmarks: settings.bookmarks.into_iter().map(|b| b.into()).collect(),
+            trash: settings.trash.into_iter().map(|t| t.into()).collect(),
+            bookmark_id_counter: 0,
+            group_id_counter: 0,
+        }
+    }
+}
+
+// Trashed bookmarks and groups are converted like the live ones
+impl From<Group> for settings::Group {
+    fn from(g: Group) -> Self {
+        Self {
+            id: g.id,
+            icon_path: g.icon_path,
+            tint_icon: g.tint_icon,
+            name: g.name,
+            bookmarks_ids: g.bookmarks_ids,
+            groups_ids: g.groups_ids,
+            aliases: g.aliases,
+            description: g.description,
+            action_mode: g.action_mode.into(),
+            launch_command: g.launch_command,
+        }
+    }
+}
+
+impl From<Bookmark> for settings::Bookmark {
+    fn from(b: Bookmark) -> Self {
+        Self {
+            id: b.id,
+            icon_path: b.icon_path,
+            name: b.name,
+            url: b.url,
+            created_at: b.created_at,
+            tags: b.tags,
+            keyword: b.keyword,
+            aliases: b.aliases,
+            description: b.description,
+            action_mode: b.action_mode.into(),
+            launch_command: b.launch_command,
+            link_check: b.link_check.map(|c| c.into()),
+        }
+    }
+}
+
+impl From<TrashItem> for settings::trash::TrashItem {
+    fn from(item: TrashItem) -> Self {
+        let entry = match item.entry {
+            TrashEntry::Bookmark {
+                bookmark,
+                groups_ids,
+            } => settings::trash::TrashEntry::Bookmark {
+                bookmark: bookmark.into(),
+                groups_ids,
+            },
+            TrashEntry::Group { group, parents_ids } => settings::trash::TrashEntry::Group {
+                group: group.into(),
+                parents_ids,
+            },
+        };
+
+        Self {
+            deleted_at: item.deleted_at,
+            entry,
+        }
+    }
+}
+
+impl From<ActionMode> for settings::ActionMode {
+    fn from(mode: ActionMode) -> Self {
+        match mode {
+            ActionMode::Default => Self::Default,
+            ActionMode::Open => Self::Open,
+            ActionMode::CopyUrl => Self::CopyUrl,
+            ActionMode::CopyMarkdown => Self::CopyMarkdown,
+        }
+    }
+}
+
+impl From<LinkCheck> for settings::LinkCheck {
+    fn from(check: LinkCheck) -> Self {
+        Self {
+            status: check.status,
+            error: check.error,
+            redirect_url: check.redirect_url,
+            permanent_redirect: check.permanent_redirect,
+            checked_at: check.checked_at,
+        }
+    }
+}
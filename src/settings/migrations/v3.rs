@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings;
+
+use super::v4;
+
+/// Layout of version 3, which added `Bookmark::tags`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub groups: Vec<Group>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Group {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub tint_icon: bool,
+    pub name: String,
+    pub bookmarks_ids: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub name: String,
+    pub url: String,
+    pub created_at: Option<u64>,
+    pub tags: Vec<String>,
+}
+
+impl Settings {
+    pub fn upgrade(self) -> settings::Settings {
+        v4::Settings::from(self).upgrade()
+    }
+}
+
+impl From<Settings> for v4::Settings {
+    fn from(settings: Settings) -> Self {
+        Self {
+            groups: settings
+                .groups
+                .into_iter()
+                .map(|g| v4::Group {
+                    id: g.id,
+                    icon_path: g.icon_path,
+                    tint_icon: g.tint_icon,
+                    name: g.name,
+                    bookmarks_ids: g.bookmarks_ids,
+                    groups_ids: Vec::new(),
+                })
+                .collect(),
+            bookmarks: settings
+                .bookmarks
+                .into_iter()
+                .map(|b| v4::Bookmark {
+                    id: b.id,
+                    icon_path: b.icon_path,
+                    name: b.name,
+                    url: b.url,
+                    created_at: b.created_at,
+                    tags: b.tags,
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings;
+
+use super::v6;
+
+/// Layout of version 5, which added `Bookmark::keyword`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub groups: Vec<Group>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Group {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub tint_icon: bool,
+    pub name: String,
+    pub bookmarks_ids: Vec<usize>,
+    pub groups_ids: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub name: String,
+    pub url: String,
+    pub created_at: Option<u64>,
+    pub tags: Vec<String>,
+    pub keyword: Option<String>,
+}
+
+impl Settings {
+    pub fn upgrade(self) -> settings::Settings {
+        v6::Settings::from(self).upgrade()
+    }
+}
+
+impl From<Settings> for v6::Settings {
+    fn from(settings: Settings) -> Self {
+        Self {
+            groups: settings
+                .groups
+                .into_iter()
+                .map(|g| v6::Group {
+                    id: g.id,
+                    icon_path: g.icon_path,
+                    tint_icon: g.tint_icon,
+                    name: g.name,
+                    bookmarks_ids: g.bookmarks_ids,
+                    groups_ids: g.groups_ids,
+                    aliases: Vec::new(),
+                })
+                .collect(),
+            bookmarks: settings
+                .bookmarks
+                .into_iter()
+                .map(|b| v6::Bookmark {
+                    id: b.id,
+                    icon_path: b.icon_path,
+                    name: b.name,
+                    url: b.url,
+                    created_at: b.created_at,
+                    tags: b.tags,
+                    keyword: b.keyword,
+                    aliases: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings;
+
+use super::v10;
+
+/// Layout of version 9, which added `Bookmark::action_mode` and `Group::action_mode`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub groups: Vec<Group>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Group {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub tint_icon: bool,
+    pub name: String,
+    pub bookmarks_ids: Vec<usize>,
+    pub groups_ids: Vec<usize>,
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub action_mode: ActionMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    pub id: usize,
+    pub icon_path: Option<String>,
+    pub name: String,
+    pub url: String,
+    pub created_at: Option<u64>,
+    pub tags: Vec<String>,
+    pub keyword: Option<String>,
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub action_mode: ActionMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ActionMode {
+    Default,
+    Open,
+    CopyUrl,
+    CopyMarkdown,
+}
+
+impl Settings {
+    pub fn upgrade(self) -> settings::Settings {
+        v10::Settings::from(self).upgrade()
+    }
+}
+
+impl From<Settings> for v10::Settings {
+    fn from(settings: Settings) -> Self {
+        Self {
+            groups: settings
+                .groups
+                .into_iter()
+                .map(|g| v10::Group {
+                    id: g.id,
+                    icon_path: g.icon_path,
+                    tint_icon: g.tint_icon,
+                    name: g.name,
+                    bookmarks_ids: g.bookmarks_ids,
+                    groups_ids: g.groups_ids,
+                    aliases: g.aliases,
+                    description: g.description,
+                    action_mode: g.action_mode.into(),
+                    launch_command: None,
+                })
+                .collect(),
+            bookmarks: settings
+                .bookmarks
+                .into_iter()
+                .map(|b| v10::Bookmark {
+                    id: b.id,
+                    icon_path: b.icon_path,
+                    name: b.name,
+                    url: b.url,
+                    created_at: b.created_at,
+                    tags: b.tags,
+                    keyword: b.keyword,
+                    aliases: b.aliases,
+                    description: b.description,
+                    action_mode: b.action_mode.into(),
+                    launch_command: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<ActionMode> for v10::ActionMode {
+    fn from(mode: ActionMode) -> Self {
+        match mode {
+            ActionMode::Default => Self::Default,
+            ActionMode::Open => Self::Open,
+            ActionMode::CopyUrl => Self::CopyUrl,
+            ActionMode::CopyMarkdown => Self::CopyMarkdown,
+        }
+    }
+}
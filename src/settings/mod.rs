@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use self::functions::get_settings;
+use crate::{error::Error, urls::normalize_url};
+
+use trash::{TrashEntry, TrashItem};
 
 pub mod functions;
+pub mod migrations;
+pub mod storage;
+pub mod trash;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Settings {
@@ -10,6 +15,15 @@ pub struct Settings {
     pub groups: Vec<Group>,
     #[serde(default = "default_bookmarks")]
     pub bookmarks: Vec<Bookmark>,
+    /// Deleted bookmarks and groups that can still be restored
+    #[serde(default)]
+    pub trash: Vec<TrashItem>,
+    /// Lowest bookmark id never handed out, so ids of deleted bookmarks aren't reused
+    #[serde(default)]
+    pub bookmark_id_counter: usize,
+    /// Lowest group id never handed out, so ids of deleted groups aren't reused
+    #[serde(default)]
+    pub group_id_counter: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -19,32 +33,421 @@ pub struct Group {
     pub tint_icon: bool,
     pub name: String,
     pub bookmarks_ids: Vec<usize>,
+    /// Groups opened together with this one
+    #[serde(default)]
+    pub groups_ids: Vec<usize>,
+    /// Searching one of them exactly always shows this group first
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub action_mode: ActionMode,
+    /// Opens the urls with this command instead of the default browser
+    #[serde(default)]
+    pub launch_command: Option<String>,
 }
 
-impl Group {
-    pub fn new(name: impl Into<String>, bookmarks_ids: Vec<usize>) -> Self {
-        let groups = get_settings().groups;
-        let last_group = groups.iter().max_by_key(|group| group.id);
+/// What selecting a bookmark or group does
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ActionMode {
+    /// Follows the `copy-url` setting
+    #[default]
+    Default,
+    Open,
+    CopyUrl,
+    CopyMarkdown,
+}
+
+impl ActionMode {
+    pub const ALL: [ActionMode; 4] = [
+        ActionMode::Default,
+        ActionMode::Open,
+        ActionMode::CopyUrl,
+        ActionMode::CopyMarkdown,
+    ];
+
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "open" => ActionMode::Open,
+            "copy-url" => ActionMode::CopyUrl,
+            "copy-markdown" => ActionMode::CopyMarkdown,
+            _ => ActionMode::Default,
+        }
+    }
+
+    pub fn get_id(&self) -> &'static str {
+        match self {
+            ActionMode::Default => "default",
+            ActionMode::Open => "open",
+            ActionMode::CopyUrl => "copy-url",
+            ActionMode::CopyMarkdown => "copy-markdown",
+        }
+    }
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            ActionMode::Default => "Use the Copy Url setting",
+            ActionMode::Open => "Open",
+            ActionMode::CopyUrl => "Copy url",
+            ActionMode::CopyMarkdown => "Copy Markdown link",
+        }
+    }
+
+    /// Replaces `Default` with what the `copy-url` setting says
+    pub fn resolve(self, copy_url: bool) -> Self {
+        match self {
+            ActionMode::Default if copy_url => ActionMode::CopyUrl,
+            ActionMode::Default => ActionMode::Open,
+            mode => mode,
+        }
+    }
+}
+
+impl Settings {
+    /// Never returns the id of a deleted group, even when it was the last one
+    pub fn next_group_id(&self) -> usize {
+        let trashed_ids = self.trash.iter().filter_map(|item| match &item.entry {
+            TrashEntry::Group { group, .. } => Some(group.id),
+            _ => None,
+        });
+
+        match self
+            .groups
+            .iter()
+            .map(|group| group.id)
+            .chain(trashed_ids)
+            .max()
+        {
+            Some(id) => self.group_id_counter.max(id + 1),
+            None => self.group_id_counter,
+        }
+    }
+
+    /// Never returns the id of a deleted bookmark, even when it was the last one
+    pub fn next_bookmark_id(&self) -> usize {
+        let trashed_ids = self.trash.iter().filter_map(|item| match &item.entry {
+            TrashEntry::Bookmark { bookmark, .. } => Some(bookmark.id),
+            _ => None,
+        });
+
+        match self
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.id)
+            .chain(trashed_ids)
+            .max()
+        {
+            Some(id) => self.bookmark_id_counter.max(id + 1),
+            None => self.bookmark_id_counter,
+        }
+    }
+
+    /// Remembers the ids handed out so far. Called before every write
+    pub fn update_id_counters(&mut self) {
+        self.bookmark_id_counter = self.next_bookmark_id();
+        self.group_id_counter = self.next_group_id();
+    }
+
+    /// Removes group members that no longer exist. Older versions left them behind on delete,
+    /// and a new bookmark or group reusing the id would silently join those groups
+    pub fn remove_dangling_references(&mut self) {
+        let max_bookmark_id = self.groups.iter().flat_map(|g| &g.bookmarks_ids).max();
+        let max_group_id = self.groups.iter().flat_map(|g| &g.groups_ids).max();
+
+        // Skip the dangling ids as well, like the usage counts still point at them
+        if let Some(id) = max_bookmark_id {
+            self.bookmark_id_counter = self.bookmark_id_counter.max(id + 1);
+        }
+
+        if let Some(id) = max_group_id {
+            self.group_id_counter = self.group_id_counter.max(id + 1);
+        }
+
+        self.update_id_counters();
+
+        let bookmarks_ids: Vec<usize> = self.bookmarks.iter().map(|b| b.id).collect();
+        let groups_ids: Vec<usize> = self.groups.iter().map(|g| g.id).collect();
+
+        for group in self.groups.iter_mut() {
+            group.bookmarks_ids.retain(|id| bookmarks_ids.contains(id));
+            group.groups_ids.retain(|id| groups_ids.contains(id));
+        }
+    }
+
+    /// Checks that no other bookmark uses the keyword and that it isn't a reserved one
+    pub fn validate_keyword(&self, bookmark_id: usize, keyword: &str) -> Result<(), Error> {
+        if keyword.contains(char::is_whitespace) || RESERVED_KEYWORDS.contains(&keyword) {
+            return Err(Error::InvalidKeyword(keyword.to_string()));
+        }
+
+        let taken = self
+            .bookmarks
+            .iter()
+            .any(|b| b.id != bookmark_id && b.keyword.as_deref() == Some(keyword));
+
+        if taken {
+            return Err(Error::KeywordTaken(keyword.to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub fn find_by_keyword(&self, keyword: &str) -> Option<&Bookmark> {
+        let keyword = keyword.to_lowercase();
+
+        self.bookmarks
+            .iter()
+            .find(|b| b.keyword.as_deref() == Some(keyword.as_str()))
+    }
+
+    /// Checks that no other bookmark or group uses any of the aliases
+    pub fn validate_aliases(&self, owner: AliasOwner, aliases: &[String]) -> Result<(), Error> {
+        for alias in aliases {
+            let bookmark_owner = self
+                .bookmarks
+                .iter()
+                .find(|b| owner != AliasOwner::Bookmark(b.id) && b.has_alias(alias));
+
+            let group_owner = self
+                .groups
+                .iter()
+                .find(|g| owner != AliasOwner::Group(g.id) && g.has_alias(alias));
+
+            if let Some(bookmark) = bookmark_owner {
+                return Err(Error::AliasTaken(
+                    alias.to_owned(),
+                    bookmark.name.to_owned(),
+                ));
+            }
 
-        let new_id = match last_group {
-            Some(group) => group.id + 1,
-            None => 0,
+            if let Some(group) = group_owner {
+                return Err(Error::AliasTaken(alias.to_owned(), group.name.to_owned()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Another bookmark that points to the same page as `url`
+    pub fn find_duplicate(&self, bookmark_id: usize, url: &str) -> Option<&Bookmark> {
+        let key = normalize_url(url);
+
+        self.bookmarks
+            .iter()
+            .find(|b| b.id != bookmark_id && normalize_url(&b.url) == key)
+    }
+
+    /// Groups of bookmarks that point to the same page, oldest first
+    pub fn get_duplicate_clusters(&self) -> Vec<Vec<&Bookmark>> {
+        let mut clusters = Vec::<(String, Vec<&Bookmark>)>::new();
+
+        for bookmark in &self.bookmarks {
+            let key = normalize_url(&bookmark.url);
+
+            match clusters.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, cluster)) => cluster.push(bookmark),
+                None => clusters.push((key, vec![bookmark])),
+            }
+        }
+
+        clusters
+            .into_iter()
+            .map(|(_, cluster)| cluster)
+            .filter(|cluster| cluster.len() > 1)
+            .collect()
+    }
+
+    /// Keeps the first bookmark of `bookmarks_ids`, moving the group memberships, tags and
+    /// aliases of the others to it. Returns the bookmarks that were merged away
+    pub fn merge_bookmarks(&mut self, bookmarks_ids: &[usize]) -> Result<Vec<Bookmark>, Error> {
+        let (keep_id, merged_ids) = match bookmarks_ids.split_first() {
+            Some(split) => split,
+            None => return Err(Error::MissingArg("id".to_string())),
+        };
+
+        if !self.bookmarks.iter().any(|b| b.id == *keep_id) {
+            return Err(Error::BookmarkNotFound(*keep_id));
+        }
+
+        let merged: Vec<Bookmark> = self
+            .bookmarks
+            .iter()
+            .filter(|b| merged_ids.contains(&b.id))
+            .cloned()
+            .collect();
+
+        for group in self.groups.iter_mut() {
+            let mut bookmarks_ids = Vec::<usize>::new();
+
+            for id in &group.bookmarks_ids {
+                let id = if merged_ids.contains(id) {
+                    *keep_id
+                } else {
+                    *id
+                };
+
+                if !bookmarks_ids.contains(&id) {
+                    bookmarks_ids.push(id);
+                }
+            }
+
+            group.bookmarks_ids = bookmarks_ids;
+        }
+
+        if let Some(kept) = self.bookmarks.iter_mut().find(|b| b.id == *keep_id) {
+            for bookmark in &merged {
+                for tag in &bookmark.tags {
+                    if !kept.has_tag(tag) {
+                        kept.tags.push(tag.to_owned());
+                    }
+                }
+
+                for alias in &bookmark.aliases {
+                    if !kept.aliases.contains(alias) {
+                        kept.aliases.push(alias.to_owned());
+                    }
+                }
+
+                if kept.keyword.is_none() {
+                    kept.keyword = bookmark.keyword.to_owned();
+                }
+
+                if kept.description.is_none() {
+                    kept.description = bookmark.description.to_owned();
+                }
+            }
+        }
+
+        self.bookmarks.retain(|b| !merged_ids.contains(&b.id));
+
+        Ok(merged)
+    }
+
+    /// Whether `group_id` would end up inside itself if it contained `groups_ids`
+    pub fn creates_cycle(&self, group_id: usize, groups_ids: &[usize]) -> bool {
+        let mut pending = groups_ids.to_vec();
+        let mut visited = Vec::<usize>::new();
+
+        while let Some(id) = pending.pop() {
+            if id == group_id {
+                return true;
+            }
+
+            if visited.contains(&id) {
+                continue;
+            }
+
+            visited.push(id);
+
+            if let Some(group) = self.groups.iter().find(|g| g.id == id) {
+                pending.extend(&group.groups_ids);
+            }
+        }
+
+        false
+    }
+
+    /// Urls of the group and every group inside it, without duplicates
+    pub fn get_group_urls(&self, group_id: usize) -> Vec<String> {
+        self.get_group_bookmarks(group_id)
+            .into_iter()
+            .map(|bookmark| bookmark.url.to_owned())
+            .collect()
+    }
+
+    /// Bookmarks of the group and every group inside it, skipping repeated urls
+    pub fn get_group_bookmarks(&self, group_id: usize) -> Vec<&Bookmark> {
+        let mut bookmarks = Vec::<&Bookmark>::new();
+        let mut visited = Vec::<usize>::new();
+
+        self.collect_group_bookmarks(group_id, &mut visited, &mut bookmarks);
+
+        bookmarks
+    }
+
+    fn collect_group_bookmarks<'a>(
+        &'a self,
+        group_id: usize,
+        visited: &mut Vec<usize>,
+        bookmarks: &mut Vec<&'a Bookmark>,
+    ) {
+        // Files edited by hand can still contain cycles
+        if visited.contains(&group_id) {
+            return;
+        }
+
+        visited.push(group_id);
+
+        let group = match self.groups.iter().find(|g| g.id == group_id) {
+            Some(group) => group,
+            None => return,
         };
 
+        for bookmark_id in &group.bookmarks_ids {
+            if let Some(bookmark) = self.bookmarks.iter().find(|b| b.id == *bookmark_id) {
+                if !bookmarks.iter().any(|b| b.url == bookmark.url) {
+                    bookmarks.push(bookmark);
+                }
+            }
+        }
+
+        for child_id in &group.groups_ids {
+            self.collect_group_bookmarks(*child_id, visited, bookmarks);
+        }
+    }
+}
+
+impl Group {
+    pub fn new(id: usize, name: impl Into<String>, bookmarks_ids: Vec<usize>) -> Self {
         Self {
-            id: new_id,
+            id,
             icon_path: None,
             tint_icon: false,
             name: name.into(),
             bookmarks_ids,
+            groups_ids: Vec::new(),
+            aliases: Vec::new(),
+            description: None,
+            action_mode: ActionMode::Default,
+            launch_command: None,
         }
     }
 
+    pub fn set_groups_ids(mut self, groups_ids: Vec<usize>) -> Self {
+        self.groups_ids = groups_ids;
+        self.to_owned()
+    }
+
     pub fn set_icon_path(mut self, icon_path: impl Into<String>) -> Self {
         self.icon_path = Some(icon_path.into());
         self.to_owned()
     }
 
+    pub fn set_aliases(mut self, aliases: Vec<String>) -> Self {
+        self.aliases = aliases;
+        self.to_owned()
+    }
+
+    pub fn has_alias(&self, search_text: &str) -> bool {
+        is_alias(&self.aliases, search_text)
+    }
+
+    pub fn set_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into()).filter(|d| !d.trim().is_empty());
+        self.to_owned()
+    }
+
+    pub fn set_action_mode(mut self, action_mode: ActionMode) -> Self {
+        self.action_mode = action_mode;
+        self.to_owned()
+    }
+
+    pub fn set_launch_command(mut self, launch_command: Option<String>) -> Self {
+        self.launch_command = launch_command;
+        self.to_owned()
+    }
+
     pub fn set_tint_icon(&mut self, tint_icon: bool) -> Self {
         self.tint_icon = tint_icon;
         self.to_owned()
@@ -57,22 +460,73 @@ pub struct Bookmark {
     pub icon_path: Option<String>,
     pub name: String,
     pub url: String,
+    /// Unix timestamp in seconds, only known for imported bookmarks
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Typing it as the first word opens the url with `%s` replaced by the rest of the search
+    #[serde(default)]
+    pub keyword: Option<String>,
+    /// Searching one of them exactly always shows this bookmark first
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub action_mode: ActionMode,
+    /// Opens the urls with this command instead of the default browser
+    #[serde(default)]
+    pub launch_command: Option<String>,
+    /// Result of the last link check
+    #[serde(default)]
+    pub link_check: Option<LinkCheck>,
 }
 
-impl Bookmark {
-    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
-        let bookmarks = get_settings().bookmarks;
-        let last_bookmark = bookmarks.iter().max_by_key(|bookmark| bookmark.id);
-        let new_id = match last_bookmark {
-            Some(bookmark) => bookmark.id + 1,
-            None => 0,
-        };
+/// What a link check found for a bookmark url
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LinkCheck {
+    /// Status of the last response, `None` when the website couldn't be reached
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    /// Where the url ended up after following redirects
+    pub redirect_url: Option<String>,
+    /// Whether every redirect on the way was a 301 or 308
+    pub permanent_redirect: bool,
+    /// Unix timestamp in seconds
+    pub checked_at: u64,
+}
 
+impl LinkCheck {
+    pub fn is_broken(&self) -> bool {
+        self.status.is_none_or(|status| status >= 400)
+    }
+
+    /// Short reason like `404` or `connection refused`, shown in the results
+    pub fn get_reason(&self) -> String {
+        match (self.status, &self.error) {
+            (Some(status), _) => status.to_string(),
+            (None, Some(error)) => error.to_owned(),
+            (None, None) => "unreachable".to_string(),
+        }
+    }
+}
+
+impl Bookmark {
+    pub fn new(id: usize, name: impl Into<String>, url: impl Into<String>) -> Self {
         Self {
-            id: new_id,
+            id,
             icon_path: None,
             name: name.into(),
             url: url.into(),
+            created_at: None,
+            tags: Vec::new(),
+            keyword: None,
+            aliases: Vec::new(),
+            description: None,
+            action_mode: ActionMode::Default,
+            launch_command: None,
+            link_check: None,
         }
     }
 
@@ -80,6 +534,172 @@ impl Bookmark {
         self.icon_path = Some(icon_path.into());
         self.to_owned()
     }
+
+    pub fn set_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self.to_owned()
+    }
+
+    pub fn set_action_mode(mut self, action_mode: ActionMode) -> Self {
+        self.action_mode = action_mode;
+        self.to_owned()
+    }
+
+    pub fn set_launch_command(mut self, launch_command: Option<String>) -> Self {
+        self.launch_command = launch_command;
+        self.to_owned()
+    }
+
+    pub fn set_keyword(mut self, keyword: Option<String>) -> Self {
+        self.keyword = keyword;
+        self.to_owned()
+    }
+
+    pub fn set_aliases(mut self, aliases: Vec<String>) -> Self {
+        self.aliases = aliases;
+        self.to_owned()
+    }
+
+    pub fn has_alias(&self, search_text: &str) -> bool {
+        is_alias(&self.aliases, search_text)
+    }
+
+    pub fn set_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into()).filter(|d| !d.trim().is_empty());
+        self.to_owned()
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.link_check
+            .as_ref()
+            .is_some_and(|check| check.is_broken())
+    }
+
+    /// The url it permanently redirects to, if the last check found one
+    pub fn get_permanent_redirect(&self) -> Option<&str> {
+        // Search urls are checked with an empty search, so the target lost its `%s`
+        if self.is_search_url() {
+            return None;
+        }
+
+        self.link_check
+            .as_ref()
+            .filter(|check| check.permanent_redirect && !check.is_broken())
+            .and_then(|check| check.redirect_url.as_deref())
+    }
+
+    /// A `[name](url)` link, used by the copy Markdown action
+    pub fn get_markdown_link(&self, url: &str) -> String {
+        let name = self.name.replace('[', "\\[").replace(']', "\\]");
+        let url = url.replace(' ', "%20").replace(')', "%29");
+
+        format!("[{}]({})", name, url)
+    }
+
+    /// Whether the url has a `%s` or `%S` placeholder for a keyword search
+    pub fn is_search_url(&self) -> bool {
+        self.url.contains("%s") || self.url.contains("%S")
+    }
+
+    /// Replaces `%s` with the url encoded query and `%S` with the raw one, like browsers do
+    pub fn get_url_for(&self, query: &str) -> String {
+        self.url
+            .replace("%s", &encode_query(query))
+            .replace("%S", query)
+    }
+
+    /// Tags are compared ignoring case, so `#Work` finds bookmarks tagged `work`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// The item being saved, so its own aliases don't count as duplicates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AliasOwner {
+    Bookmark(usize),
+    Group(usize),
+}
+
+/// Reads aliases typed as `gr, grafana`. They are matched ignoring case
+pub fn parse_aliases(text: &str) -> Vec<String> {
+    let mut aliases = Vec::<String>::new();
+
+    for alias in text.split(|c: char| c == ',' || c.is_whitespace()) {
+        let alias = alias.trim().to_lowercase();
+
+        if !alias.is_empty() && !aliases.contains(&alias) {
+            aliases.push(alias);
+        }
+    }
+
+    aliases
+}
+
+fn is_alias(aliases: &[String], search_text: &str) -> bool {
+    let search_text = search_text.trim().to_lowercase();
+
+    aliases.contains(&search_text)
+}
+
+/// Words the results already use as keywords, so bookmarks can't take them
+pub const RESERVED_KEYWORDS: [&str; 9] = [
+    "e",
+    "edit",
+    "d",
+    "delete",
+    "i",
+    "import",
+    "broken",
+    "duplicates",
+    "trash",
+];
+
+/// Trims a typed launch command. Empty means the default browser
+pub fn parse_launch_command(text: &str) -> Option<String> {
+    Some(text.trim().to_string()).filter(|c| !c.is_empty())
+}
+
+/// Trims and lowercases a typed keyword. Empty means the bookmark has none
+pub fn parse_keyword(text: &str) -> Option<String> {
+    let keyword = text.trim().to_lowercase();
+
+    if keyword.is_empty() {
+        None
+    } else {
+        Some(keyword)
+    }
+}
+
+/// Percent encodes everything but the unreserved characters of RFC 3986
+pub fn encode_query(query: &str) -> String {
+    let mut encoded = String::new();
+
+    for byte in query.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+/// Reads tags typed as `work, docs` or `#work #docs`
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::<String>::new();
+
+    for tag in text.split(|c: char| c == ',' || c.is_whitespace()) {
+        let tag = tag.trim().trim_start_matches('#');
+
+        if !tag.is_empty() && !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    tags
 }
 
 fn default_groups() -> Vec<Group> {
@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+use super::{
+    migrations::{self, SettingsError, CURRENT_VERSION},
+    Settings,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsFormat {
+    Binary,
+    Text,
+}
+
+/// Shape of `settings.json`. The version sits next to the data so hand edited files stay readable
+#[derive(Serialize)]
+struct TextSettings {
+    version: u32,
+    #[serde(flatten)]
+    settings: Settings,
+}
+
+impl SettingsFormat {
+    pub fn encode(&self, settings: &Settings) -> Vec<u8> {
+        match self {
+            SettingsFormat::Binary => migrations::encode(settings),
+            SettingsFormat::Text => {
+                let text = TextSettings {
+                    version: CURRENT_VERSION,
+                    settings: settings.to_owned(),
+                };
+
+                serde_json::to_vec_pretty(&text).expect("Error serializing settings")
+            }
+        }
+    }
+
+    /// The format comes from the file name. Headerless version 0 binary files can start with
+    /// any byte, so the content can't tell them apart from JSON
+    pub fn decode(&self, bytes: &[u8]) -> Result<Settings, SettingsError> {
+        match self {
+            SettingsFormat::Binary => migrations::decode(bytes),
+            SettingsFormat::Text => {
+                let value: serde_json::Value = serde_json::from_slice(bytes)
+                    .map_err(|e| SettingsError::Corrupted(e.to_string()))?;
+
+                // Older files go through the same upgrades as binary ones
+                let version = value
+                    .get("version")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| SettingsError::Corrupted("Missing version".to_string()))?;
+
+                migrations::decode_text(u32::try_from(version).unwrap_or(u32::MAX), value)
+            }
+        }
+    }
+}
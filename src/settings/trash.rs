@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use whiskers_launcher_core::features::extensions::get_extension_setting;
+
+use crate::{error::Error, ID};
+
+use super::{AliasOwner, Bookmark, Group, Settings};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// A deleted bookmark or group, kept until it's restored, deleted for good or too old
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashItem {
+    /// Unix timestamp in seconds
+    pub deleted_at: u64,
+    pub entry: TrashEntry,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum TrashEntry {
+    /// `groups_ids` are the groups it was in, so restoring puts it back in them
+    Bookmark {
+        bookmark: Bookmark,
+        groups_ids: Vec<usize>,
+    },
+    /// `parents_ids` are the groups it was inside of
+    Group {
+        group: Group,
+        parents_ids: Vec<usize>,
+    },
+}
+
+impl TrashEntry {
+    pub fn get_name(&self) -> &str {
+        match self {
+            TrashEntry::Bookmark { bookmark, .. } => &bookmark.name,
+            TrashEntry::Group { group, .. } => &group.name,
+        }
+    }
+
+    pub fn is_bookmark(&self, bookmark_id: usize) -> bool {
+        matches!(self, TrashEntry::Bookmark { bookmark, .. } if bookmark.id == bookmark_id)
+    }
+
+    pub fn is_group(&self, group_id: usize) -> bool {
+        matches!(self, TrashEntry::Group { group, .. } if group.id == group_id)
+    }
+}
+
+/// Days deleted items are kept, from the `trash-retention` setting. `None` keeps them forever
+pub fn get_trash_retention() -> Option<u64> {
+    match get_extension_setting(ID, "trash-retention").as_deref() {
+        Some("never") => None,
+        Some(days) => days.parse().ok().or(Some(30)),
+        None => Some(30),
+    }
+}
+
+impl Settings {
+    /// Moves a bookmark to the trash, taking it out of its groups
+    pub fn trash_bookmark(&mut self, bookmark_id: usize, now: u64) -> Result<(), Error> {
+        let index = self
+            .bookmarks
+            .iter()
+            .position(|b| b.id == bookmark_id)
+            .ok_or(Error::BookmarkNotFound(bookmark_id))?;
+
+        let bookmark = self.bookmarks.remove(index);
+        let mut groups_ids = Vec::<usize>::new();
+
+        for group in self.groups.iter_mut() {
+            if group.bookmarks_ids.contains(&bookmark_id) {
+                group.bookmarks_ids.retain(|id| *id != bookmark_id);
+                groups_ids.push(group.id);
+            }
+        }
+
+        self.trash.push(TrashItem {
+            deleted_at: now,
+            entry: TrashEntry::Bookmark {
+                bookmark,
+                groups_ids,
+            },
+        });
+
+        Ok(())
+    }
+
+    /// Moves a group to the trash, taking it out of the groups it was inside of
+    pub fn trash_group(&mut self, group_id: usize, now: u64) -> Result<(), Error> {
+        let index = self
+            .groups
+            .iter()
+            .position(|g| g.id == group_id)
+            .ok_or(Error::GroupNotFound(group_id))?;
+
+        let group = self.groups.remove(index);
+        let mut parents_ids = Vec::<usize>::new();
+
+        for parent in self.groups.iter_mut() {
+            if parent.groups_ids.contains(&group_id) {
+                parent.groups_ids.retain(|id| *id != group_id);
+                parents_ids.push(parent.id);
+            }
+        }
+
+        self.trash.push(TrashItem {
+            deleted_at: now,
+            entry: TrashEntry::Group { group, parents_ids },
+        });
+
+        Ok(())
+    }
+
+    /// Puts a bookmark back, in the groups it was in that still exist
+    pub fn restore_bookmark(&mut self, bookmark_id: usize) -> Result<(), Error> {
+        let index = self
+            .trash
+            .iter()
+            .position(|item| item.entry.is_bookmark(bookmark_id))
+            .ok_or(Error::BookmarkNotFound(bookmark_id))?;
+
+        if let TrashEntry::Bookmark {
+            mut bookmark,
+            groups_ids,
+        } = self.trash.remove(index).entry
+        {
+            // Another bookmark could have taken its keyword or aliases in the meantime
+            let keyword_taken = bookmark
+                .keyword
+                .as_deref()
+                .is_some_and(|keyword| self.validate_keyword(bookmark.id, keyword).is_err());
+
+            if keyword_taken {
+                bookmark.keyword = None;
+            }
+
+            bookmark.aliases =
+                self.get_free_aliases(AliasOwner::Bookmark(bookmark.id), bookmark.aliases);
+
+            for group in self.groups.iter_mut() {
+                if groups_ids.contains(&group.id) && !group.bookmarks_ids.contains(&bookmark.id) {
+                    group.bookmarks_ids.push(bookmark.id);
+                }
+            }
+
+            self.bookmarks.push(bookmark);
+        }
+
+        Ok(())
+    }
+
+    /// Puts a group back inside the groups it was in that still exist. Members deleted since
+    /// then are left out
+    pub fn restore_group(&mut self, group_id: usize) -> Result<(), Error> {
+        let index = self
+            .trash
+            .iter()
+            .position(|item| item.entry.is_group(group_id))
+            .ok_or(Error::GroupNotFound(group_id))?;
+
+        if let TrashEntry::Group {
+            mut group,
+            parents_ids,
+        } = self.trash.remove(index).entry
+        {
+            group
+                .bookmarks_ids
+                .retain(|id| self.bookmarks.iter().any(|b| b.id == *id));
+            group
+                .groups_ids
+                .retain(|id| self.groups.iter().any(|g| g.id == *id));
+            group.aliases = self.get_free_aliases(AliasOwner::Group(group.id), group.aliases);
+
+            self.groups.push(group);
+
+            // The parents could have been put inside this group while it was in the trash
+            for parent_id in parents_ids {
+                if self.creates_cycle(parent_id, &[group_id]) {
+                    continue;
+                }
+
+                if let Some(parent) = self.groups.iter_mut().find(|g| g.id == parent_id) {
+                    parent.groups_ids.push(group_id);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_free_aliases(&self, owner: AliasOwner, aliases: Vec<String>) -> Vec<String> {
+        aliases
+            .into_iter()
+            .filter(|alias| {
+                self.validate_aliases(owner, std::slice::from_ref(alias))
+                    .is_ok()
+            })
+            .collect()
+    }
+
+    /// Removes a bookmark from the trash for good and returns it
+    pub fn delete_trashed_bookmark(&mut self, bookmark_id: usize) -> Result<Bookmark, Error> {
+        let index = self
+            .trash
+            .iter()
+            .position(|item| item.entry.is_bookmark(bookmark_id))
+            .ok_or(Error::BookmarkNotFound(bookmark_id))?;
+
+        match self.trash.remove(index).entry {
+            TrashEntry::Bookmark { bookmark, .. } => Ok(bookmark),
+            TrashEntry::Group { .. } => Err(Error::BookmarkNotFound(bookmark_id)),
+        }
+    }
+
+    /// Removes a group from the trash for good
+    pub fn delete_trashed_group(&mut self, group_id: usize) -> Result<(), Error> {
+        let count = self.trash.len();
+        self.trash.retain(|item| !item.entry.is_group(group_id));
+
+        if self.trash.len() == count {
+            return Err(Error::GroupNotFound(group_id));
+        }
+
+        Ok(())
+    }
+
+    /// Deletes items older than `retention_days` and returns them
+    pub fn purge_trash(&mut self, now: u64, retention_days: u64) -> Vec<TrashItem> {
+        let max_age = retention_days * DAY;
+
+        let (purged, kept) = self
+            .trash
+            .drain(..)
+            .partition(|item| now.saturating_sub(item.deleted_at) >= max_age);
+
+        self.trash = kept;
+
+        purged
+    }
+}
@@ -0,0 +1,222 @@
+use url::Url;
+
+use crate::error::{Error, Result};
+
+/// Schemes whose urls have no host, like `mailto:someone@example.com`
+const HOSTLESS_SCHEMES: [&str; 5] = ["file", "mailto", "tel", "sms", "magnet"];
+
+/// Parses a url, adding `https://` when it has no scheme, like `github.com`.
+/// Internationalized domains are converted to punycode
+pub fn parse_url(url: &str) -> Result<Url> {
+    let url = url.trim();
+    let invalid = || Error::InvalidUrl(url.to_string());
+
+    // Only the start counts, so `example.com/login?next=https://foo.com` still gets https
+    let scheme = url.split_once(':').filter(|(scheme, _)| is_scheme(scheme));
+
+    let has_scheme = scheme.is_some_and(|(scheme, rest)| {
+        rest.starts_with("//") || HOSTLESS_SCHEMES.contains(&scheme.to_lowercase().as_str())
+    });
+
+    // Catches typos like `htps:/foo`, which would otherwise become a host called `htps`
+    let has_broken_scheme = scheme.is_some_and(|(_, rest)| rest.starts_with('/'));
+
+    if !has_scheme && has_broken_scheme {
+        return Err(invalid());
+    }
+
+    let parsed = if has_scheme {
+        Url::parse(url).map_err(|_| invalid())?
+    } else {
+        Url::parse(&format!("https://{}", url)).map_err(|_| invalid())?
+    };
+
+    // Urls like `http:` parse but can't be opened
+    let has_host = parsed.host_str().is_some_and(|host| !host.is_empty());
+
+    if !has_host && !HOSTLESS_SCHEMES.contains(&parsed.scheme()) {
+        return Err(invalid());
+    }
+
+    Ok(parsed)
+}
+
+fn is_scheme(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// The form urls are saved in, like `https://github.com/` for `github.com`
+pub fn canonicalize_url(url: &str) -> Result<String> {
+    Ok(parse_url(url)?.to_string())
+}
+
+/// Key used to tell whether two urls point to the same page. `github.com`,
+/// `https://github.com/` and `https://www.github.com:443` all give `github.com`
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+
+    let parsed = match parse_url(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.to_lowercase(),
+    };
+
+    // Urls like `mailto:` have no host and are compared as they are
+    let host = match parsed.host_str() {
+        Some(host) => host.strip_prefix("www.").unwrap_or(host),
+        None => return parsed.to_string(),
+    };
+
+    let mut key = String::new();
+
+    // http and https usually serve the same page, other schemes don't
+    if !matches!(parsed.scheme(), "http" | "https") {
+        key.push_str(parsed.scheme());
+        key.push_str("://");
+    }
+
+    key.push_str(host);
+
+    // Default ports are already dropped by the parser
+    if let Some(port) = parsed.port() {
+        key.push_str(&format!(":{}", port));
+    }
+
+    key.push_str(parsed.path().trim_end_matches('/'));
+
+    if let Some(query) = parsed.query() {
+        key.push('?');
+        key.push_str(query);
+    }
+
+    // Hash routed pages, like `#inbox` and `#sent`, are different pages
+    if let Some(fragment) = parsed.fragment().filter(|f| !f.is_empty()) {
+        key.push('#');
+        key.push_str(fragment);
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_https_when_missing() {
+        assert_eq!(
+            parse_url("github.com").unwrap().as_str(),
+            "https://github.com/"
+        );
+        assert_eq!(
+            parse_url("  http://github.com/rust  ").unwrap().as_str(),
+            "http://github.com/rust"
+        );
+    }
+
+    #[test]
+    fn rejects_broken_schemes() {
+        assert!(parse_url("htps:/foo").is_err());
+        assert!(parse_url("http://").is_err());
+        assert!(parse_url("foo bar.com").is_err());
+    }
+
+    #[test]
+    fn keeps_ports() {
+        assert_eq!(
+            parse_url("localhost:8080").unwrap().as_str(),
+            "https://localhost:8080/"
+        );
+    }
+
+    #[test]
+    fn converts_idn_hosts_to_punycode() {
+        assert_eq!(
+            parse_url("bücher.de/a").unwrap().as_str(),
+            "https://xn--bcher-kva.de/a"
+        );
+    }
+
+    #[test]
+    fn accepts_hostless_schemes() {
+        assert_eq!(
+            parse_url("mailto:someone@example.com").unwrap().as_str(),
+            "mailto:someone@example.com"
+        );
+        assert_eq!(
+            parse_url("MAILTO:someone@example.com").unwrap().scheme(),
+            "mailto"
+        );
+    }
+
+    #[test]
+    fn ignores_urls_inside_the_query() {
+        assert_eq!(
+            parse_url("example.com/login?next=https://foo.com")
+                .unwrap()
+                .as_str(),
+            "https://example.com/login?next=https://foo.com"
+        );
+    }
+
+    #[test]
+    fn normalizes_scheme_and_www() {
+        assert_eq!(normalize_url("github.com"), "github.com");
+        assert_eq!(normalize_url("http://www.GitHub.com"), "github.com");
+        assert_eq!(normalize_url("https://github.com"), "github.com");
+        assert_eq!(normalize_url("ftp://github.com"), "ftp://github.com");
+    }
+
+    #[test]
+    fn normalizes_ports() {
+        assert_eq!(normalize_url("https://github.com:443"), "github.com");
+        assert_eq!(normalize_url("http://github.com:80"), "github.com");
+        assert_eq!(normalize_url("http://github.com:8080"), "github.com:8080");
+    }
+
+    #[test]
+    fn normalizes_trailing_slashes() {
+        assert_eq!(normalize_url("https://github.com/"), "github.com");
+        assert_eq!(normalize_url("https://github.com/rust/"), "github.com/rust");
+    }
+
+    #[test]
+    fn keeps_queries() {
+        assert_eq!(
+            normalize_url("https://example.com/search?q=rust"),
+            "example.com/search?q=rust"
+        );
+        assert_ne!(
+            normalize_url("example.com/?page=1"),
+            normalize_url("example.com/?page=2")
+        );
+    }
+
+    #[test]
+    fn keeps_fragments() {
+        assert_eq!(
+            normalize_url("https://mail.google.com/mail/u/0/#inbox"),
+            "mail.google.com/mail/u/0#inbox"
+        );
+        assert_ne!(
+            normalize_url("https://mail.google.com/mail/u/0/#inbox"),
+            normalize_url("https://mail.google.com/mail/u/0/#sent")
+        );
+        assert_eq!(normalize_url("https://github.com/#"), "github.com");
+    }
+
+    #[test]
+    fn keeps_hostless_urls() {
+        assert_eq!(
+            normalize_url("mailto:someone@example.com"),
+            "mailto:someone@example.com"
+        );
+    }
+
+    #[test]
+    fn lowercases_unparsable_urls() {
+        assert_eq!(normalize_url("  Not A Url "), "not a url");
+    }
+}
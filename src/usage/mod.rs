@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use whiskers_launcher_core::features::extensions::get_extension_setting;
+
+use crate::{
+    error::Result,
+    paths::{get_usage_lock_path, get_usage_path},
+    settings::functions::{lock_file, write_atomically},
+    ID,
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// How often bookmarks and groups are opened. Kept apart from the settings so opening a
+/// bookmark doesn't rewrite them
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Usage {
+    #[serde(default)]
+    pub bookmarks: HashMap<usize, UsageEntry>,
+    #[serde(default)]
+    pub groups: HashMap<usize, UsageEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct UsageEntry {
+    pub open_count: u64,
+    /// Unix timestamp in seconds
+    pub last_opened: u64,
+}
+
+impl UsageEntry {
+    /// Open count weighted by how recently it was last opened, like browsers rank their history
+    pub fn get_frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_opened);
+
+        let weight = match age {
+            age if age < 4 * DAY => 100.0,
+            age if age < 14 * DAY => 70.0,
+            age if age < 31 * DAY => 50.0,
+            age if age < 90 * DAY => 30.0,
+            _ => 10.0,
+        };
+
+        self.open_count as f64 * weight
+    }
+
+    fn record_open(&mut self, now: u64) {
+        self.open_count += 1;
+        self.last_opened = now;
+    }
+}
+
+impl Usage {
+    pub fn get_bookmark_frecency(&self, bookmark_id: usize, now: u64) -> f64 {
+        self.bookmarks
+            .get(&bookmark_id)
+            .map(|entry| entry.get_frecency(now))
+            .unwrap_or_default()
+    }
+
+    pub fn get_group_frecency(&self, group_id: usize, now: u64) -> f64 {
+        self.groups
+            .get(&group_id)
+            .map(|entry| entry.get_frecency(now))
+            .unwrap_or_default()
+    }
+}
+
+pub fn is_tracking_enabled() -> bool {
+    get_extension_setting(ID, "track-usage").is_none_or(|v| v == "true")
+}
+
+pub fn get_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Usage only affects ranking, so an unreadable file is treated as empty
+pub fn get_usage() -> Usage {
+    fs::read(get_usage_path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+/// Reads, changes and writes the usage under its own lock. Opening doesn't lock the settings,
+/// so concurrent opens only wait for each other here
+fn update_usage(update: impl FnOnce(&mut Usage)) -> Result<()> {
+    let _lock = lock_file(&get_usage_lock_path())?;
+    let mut usage = get_usage();
+
+    update(&mut usage);
+
+    write_usage(&usage)
+}
+
+fn write_usage(usage: &Usage) -> Result<()> {
+    let bytes = serde_json::to_vec(usage).expect("Error serializing usage");
+    write_atomically(&get_usage_path(), &bytes)?;
+
+    Ok(())
+}
+
+pub fn record_bookmark_open(bookmark_id: usize) -> Result<()> {
+    if !is_tracking_enabled() {
+        return Ok(());
+    }
+
+    update_usage(|usage| {
+        usage
+            .bookmarks
+            .entry(bookmark_id)
+            .or_default()
+            .record_open(get_now())
+    })
+}
+
+pub fn record_group_open(group_id: usize) -> Result<()> {
+    if !is_tracking_enabled() {
+        return Ok(());
+    }
+
+    update_usage(|usage| {
+        usage
+            .groups
+            .entry(group_id)
+            .or_default()
+            .record_open(get_now())
+    })
+}